@@ -0,0 +1,58 @@
+//! Times `evaluate_difficulty_detailed`, which applies singles through
+//! `propagate_singles_counting`, against walking the same solve hint by hint
+//! through `get_hint`, as evaluations did before the fast path. Both must use
+//! the same techniques the same number of times.
+//! Run with `cargo run --release --example singles_fast_path`.
+use fast_sudoku::difficulty::{apply_hint, evaluate_difficulty_detailed};
+use fast_sudoku::generator::Generator;
+use fast_sudoku::grid::Grid;
+use fast_sudoku::solver::update_candidates;
+use fast_sudoku::techniques::get_hint;
+use std::time::{Duration, Instant};
+
+const PUZZLES: usize = 40;
+const REPEATS: u32 = 20;
+
+/// Technique use counts from the ladder alone, in order of first use.
+fn ladder_only(puzzle: &Grid) -> Vec<(&'static str, usize)> {
+    let mut grid = *puzzle;
+    update_candidates(&mut grid);
+    let mut techniques: Vec<(&'static str, usize)> = Vec::new();
+    while let Some(hint) = get_hint(&grid) {
+        match techniques.iter_mut().find(|(name, _)| *name == hint.technique) {
+            Some((_, count)) => *count += 1,
+            None => techniques.push((hint.technique, 1)),
+        }
+        apply_hint(&mut grid, &hint);
+    }
+    techniques
+}
+
+fn time<T>(puzzles: &[Grid], mut run: impl FnMut(&Grid) -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..REPEATS {
+        for puzzle in puzzles {
+            std::hint::black_box(run(puzzle));
+        }
+    }
+    start.elapsed() / (REPEATS * puzzles.len() as u32)
+}
+
+fn main() {
+    for category in ["basic", "intermediate"] {
+        let puzzles: Vec<Grid> = Generator::new_with_seed(1).puzzles(category).take(PUZZLES).map(|p| Grid::from_string(&p)).collect();
+        for puzzle in &puzzles {
+            assert_eq!(evaluate_difficulty_detailed(puzzle).techniques, ladder_only(puzzle));
+        }
+
+        let fast = time(&puzzles, evaluate_difficulty_detailed);
+        let ladder = time(&puzzles, ladder_only);
+        println!(
+            "{:<12} fast path {:>8.1?}/evaluation  ladder only {:>8.1?}/evaluation  {:.1}x",
+            category,
+            fast,
+            ladder,
+            ladder.as_secs_f64() / fast.as_secs_f64()
+        );
+    }
+}
@@ -1,54 +1,455 @@
 
 use crate::grid::Grid;
-use crate::techniques::get_hint;
-use crate::solver::update_candidates_after_move;
-use std::collections::HashSet;
+use crate::techniques::{detect_technique_all, get_basic_hint, get_hint, technique_difficulty, Hint};
+use crate::solver::{propagate_singles_counting, update_candidates_after_move, SinglesApplied};
+use crate::generator::CATEGORIES;
 
+#[derive(Debug, Clone, Copy)]
 pub struct DifficultyResult {
     pub score: i32,
     pub solvable: bool,
+    pub hardest_technique: &'static str, // Empty if no step was needed
+    pub valid: bool,                     // False if givens repeat in a unit; nothing is rated then
+    pub unique: Option<bool>,            // Only checked by `evaluate_difficulty_checked`
 }
 
+impl DifficultyResult {
+    /// Result for a grid that could not be rated: score 0, nothing solvable.
+    fn unrated(valid: bool, unique: Option<bool>) -> Self {
+        DifficultyResult { score: 0, solvable: false, hardest_technique: "", valid, unique }
+    }
+
+    /// `classify`'s category, or `INVALID_CATEGORY` for an unrated grid.
+    pub fn category(&self) -> &'static str {
+        if self.valid { classify(self.score) } else { INVALID_CATEGORY }
+    }
+}
+
+/// Category reported for grids whose givens repeat in a unit, which have no
+/// score to classify.
+pub const INVALID_CATEGORY: &str = "invalid";
+
+/// Weights for turning a solve path into a score. The default reproduces the
+/// standard ratings that `CATEGORIES` is calibrated against.
+#[derive(Debug, Clone, Copy)]
+pub struct ScoringConfig {
+    pub max_weight: f32, // Per point of the hardest technique's difficulty
+    pub avg_weight: f32, // Per point of the mean step difficulty
+    /// Points added per solve step, so long grinds rate above short solves
+    /// with the same hardest technique. Typical paths run 60-80 steps, so
+    /// 0.05 adds about 3 points. 0 by default.
+    pub length_penalty: f32,
+    /// Points per doubling of the largest `Hint::search_cost` on the path,
+    /// separating patterns that are hard to find from ones that are hard to
+    /// apply. Subsets cost up to a few hundred and Y-Wing triples several
+    /// thousand, so 1.0 adds up to about 13 points. 0 by default.
+    pub search_weight: f32,
+    /// When logic runs out, score the path taken so far instead of rating 100,
+    /// modelling a solver who never guesses. `solvable: false` still marks that
+    /// logic stalled, so puzzles that need guessing can be ranked by how far
+    /// they get. Off by default: the generator's top bands rely on the 100.
+    pub score_partial_path: bool,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        ScoringConfig { max_weight: 0.7, avg_weight: 0.2, length_penalty: 0.0, search_weight: 0.0, score_partial_path: false }
+    }
+}
+
+/// Full outcome of a rating run, for callers that need more than the score.
+#[derive(Debug, Clone)]
+pub struct DetailedDifficulty {
+    pub score: i32,
+    pub solvable: bool,
+    pub hardest_technique: &'static str, // Empty if no step was needed
+    pub techniques: Vec<(&'static str, usize)>, // Use counts, in order of first use
+    pub steps: usize,
+    pub guess_depth: Option<usize>, // Only computed by `evaluate_with_guess_depth`
+    pub valid: bool,        // False if givens repeat in a unit; nothing is rated then
+}
+
+impl DetailedDifficulty {
+    fn invalid() -> Self {
+        DetailedDifficulty { score: 0, solvable: false, hardest_technique: "", techniques: Vec::new(), steps: 0, guess_depth: None, valid: false }
+    }
+
+    /// `classify`'s category, or `INVALID_CATEGORY` for an unrated grid.
+    pub fn category(&self) -> &'static str {
+        if self.valid { classify(self.score) } else { INVALID_CATEGORY }
+    }
+}
+
+/// True if `puzzle` rates into `expected_category`. Backs the rating
+/// regression corpus in `tests/fixtures/ratings.txt`.
+pub fn verify_rating(puzzle: &str, expected_category: &str) -> bool {
+    evaluate_difficulty(&Grid::from_string(puzzle)).category() == expected_category
+}
+
+/// Category for a score: the band containing it, preferring the nearer target
+/// where bands overlap, or the nearest target if it falls in a gap. Scores
+/// only; use the results' `category` so invalid grids aren't called trivial.
+pub fn classify(score: i32) -> &'static str {
+    let mut best = CATEGORIES[0].0;
+    let mut best_key = (true, i32::MAX);
+    for &(name, target, tolerance) in CATEGORIES {
+        let distance = (score - target).abs();
+        let key = (distance > tolerance, distance);
+        if key < best_key {
+            best_key = key;
+            best = name;
+        }
+    }
+    best
+}
+
+/// Score only, for the generator's hot loop; `evaluate_difficulty_detailed`
+/// adds the solve path. A grid whose givens conflict gets `valid: false`
+/// instead of a score.
 pub fn evaluate_difficulty(grid: &Grid) -> DifficultyResult {
+    rate(grid, None)
+}
+
+/// `evaluate_difficulty` for untrusted input: also requires exactly one
+/// solution, reporting `unique: Some(false)` with no score otherwise. The
+/// uniqueness check costs a full search, so the generator skips it.
+pub fn evaluate_difficulty_checked(grid: &Grid) -> DifficultyResult {
+    if !grid.is_valid() {
+        return DifficultyResult::unrated(false, None);
+    }
+    if !crate::solver::is_unique(grid) {
+        return DifficultyResult::unrated(true, Some(false));
+    }
+    DifficultyResult { unique: Some(true), ..rate(grid, None) }
+}
+
+pub fn evaluate_difficulty_detailed(grid: &Grid) -> DetailedDifficulty {
+    evaluate_difficulty_with_config(grid, &ScoringConfig::default())
+}
+
+/// `evaluate_difficulty_detailed` with custom scoring weights.
+pub fn evaluate_difficulty_with_config(grid: &Grid, config: &ScoringConfig) -> DetailedDifficulty {
+    evaluate(grid, None, config)
+}
+
+/// `evaluate_difficulty_detailed` plus `guess_depth` (see
+/// `solver::min_guess_depth`): 0 when logic finishes the grid, `None` only if
+/// the givens repeat. That search reruns the whole ladder after every guess,
+/// so it is kept off the other entry points.
+pub fn evaluate_with_guess_depth(grid: &Grid) -> DetailedDifficulty {
+    let mut detailed = evaluate_difficulty_detailed(grid);
+    if detailed.valid {
+        detailed.guess_depth = Some(if detailed.solvable { 0 } else { crate::solver::min_guess_depth(grid) });
+    }
+    detailed
+}
+
+/// Like `evaluate_difficulty`, but gives up as soon as the score is certain to
+/// exceed `cap`. In that case `score` is a lower bound above `cap` and
+/// `solvable` is reported as false, since the solve was not finished.
+pub fn evaluate_difficulty_capped(grid: &Grid, cap: i32) -> DifficultyResult {
+    rate(grid, Some(cap))
+}
+
+fn rate(grid: &Grid, cap: Option<i32>) -> DifficultyResult {
+    let detailed = evaluate(grid, cap, &ScoringConfig::default());
+    DifficultyResult {
+        score: detailed.score,
+        solvable: detailed.solvable,
+        hardest_technique: detailed.hardest_technique,
+        valid: detailed.valid,
+        unique: None,
+    }
+}
+
+/// Running totals for the score of a solve path.
+struct ScoreTracker {
+    max_difficulty: f32,
+    total_difficulty: f32,
+    steps: usize,
+    max_search_cost: u32,
+    hardest_technique: &'static str,
+    techniques: Vec<(&'static str, usize)>,
+    config: ScoringConfig,
+}
+
+impl ScoreTracker {
+    fn new(config: ScoringConfig) -> Self {
+        ScoreTracker {
+            max_difficulty: 0.0,
+            total_difficulty: 0.0,
+            steps: 0,
+            max_search_cost: 0,
+            hardest_technique: "",
+            techniques: Vec::new(),
+            config,
+        }
+    }
+
+    fn record(&mut self, technique: &'static str, difficulty: f32, count: usize) {
+        if count == 0 { return; }
+        if difficulty > self.max_difficulty {
+            self.max_difficulty = difficulty;
+            self.hardest_technique = technique;
+        }
+        self.total_difficulty += difficulty * count as f32;
+        self.steps += count;
+        match self.techniques.iter_mut().find(|(name, _)| *name == technique) {
+            Some(entry) => entry.1 += count,
+            None => self.techniques.push((technique, count)),
+        }
+    }
+
+    fn record_hint(&mut self, hint: &Hint) {
+        self.record(hint.technique, hint.difficulty, 1);
+        self.max_search_cost = self.max_search_cost.max(hint.search_cost);
+    }
+
+    /// Records a `propagate_singles_counting` pass in the order the ladder would
+    /// have found them.
+    fn record_singles(&mut self, singles: SinglesApplied) {
+        for technique in singles.order.into_iter().flatten() {
+            let count = match technique {
+                "full_house" => singles.full_house,
+                "naked_single" => singles.naked,
+                _ => singles.hidden,
+            };
+            self.record(technique, technique_difficulty(technique), count);
+        }
+    }
+
+    fn diversity_bonus(&self) -> f32 {
+        (self.techniques.len() as f32 * 0.5).min(5.0)
+    }
+
+    fn score(&self) -> i32 {
+        let avg_difficulty = if self.steps > 0 { self.total_difficulty / self.steps as f32 } else { 0.0 };
+        let weighted_score = self.max_difficulty * self.config.max_weight + avg_difficulty * self.config.avg_weight
+            + self.diversity_bonus() + self.length_term() + self.search_term();
+        (weighted_score.round() as i32).clamp(1, 100)
+    }
+
+    fn length_term(&self) -> f32 {
+        self.steps as f32 * self.config.length_penalty
+    }
+
+    fn search_term(&self) -> f32 {
+        (1.0 + self.max_search_cost as f32).log2() * self.config.search_weight
+    }
+
+    /// The max, diversity, length and search terms never decrease as the solve continues.
+    fn lower_bound(&self) -> i32 {
+        (self.max_difficulty * self.config.max_weight + self.diversity_bonus() + self.length_term()
+            + self.search_term()).round() as i32
+    }
+
+    fn finish(self, score: i32, solvable: bool) -> DetailedDifficulty {
+        DetailedDifficulty {
+            score,
+            solvable,
+            hardest_technique: self.hardest_technique,
+            techniques: self.techniques,
+            steps: self.steps,
+            guess_depth: None,
+            valid: true,
+        }
+    }
+}
+
+/// Rates the rest of a solve from the grid's current candidates instead of
+/// re-deriving them from the values, so manual eliminations are kept.
+pub fn evaluate_difficulty_from_candidates(grid: &Grid) -> DetailedDifficulty {
+    evaluate_from(*grid, None, &ScoringConfig::default())
+}
+
+fn evaluate(grid: &Grid, cap: Option<i32>, config: &ScoringConfig) -> DetailedDifficulty {
     let mut current_grid = *grid;
+    // The filled cells are the puzzle's clues, however the grid was built
+    current_grid.givens = current_grid.values.map(|v| v != 0);
     crate::solver::update_candidates(&mut current_grid);
-    
-    let mut max_difficulty = 0.0;
-    let mut total_difficulty = 0.0;
-    let mut steps = 0;
-    let mut techniques_used = HashSet::new();
+    evaluate_from(current_grid, cap, config)
+}
+
+/// Every rating path ends here, so this is where conflicting givens are
+/// turned away.
+fn evaluate_from(mut current_grid: Grid, cap: Option<i32>, config: &ScoringConfig) -> DetailedDifficulty {
+    if !current_grid.is_valid() {
+        return DetailedDifficulty::invalid();
+    }
+    let mut tracker = ScoreTracker::new(*config);
     
     loop {
+        // Singles are by far the most common step; apply them without the ladder
+        tracker.record_singles(propagate_singles_counting(&mut current_grid));
+
         if current_grid.is_solved() {
-            // Calculate score
-            let diversity_bonus = (techniques_used.len() as f32 * 0.5).min(5.0);
-            let avg_difficulty = if steps > 0 { total_difficulty / steps as f32 } else { 0.0 };
-            let weighted_score = max_difficulty * 0.7 + avg_difficulty * 0.2 + diversity_bonus;
-            let final_score = weighted_score.round() as i32;
-            return DifficultyResult { score: final_score.clamp(1, 100), solvable: true };
+            let score = tracker.score();
+            return tracker.finish(score, true);
         }
         
         if let Some(hint) = get_hint(&current_grid) {
-            max_difficulty = max_difficulty.max(hint.difficulty);
-            total_difficulty += hint.difficulty;
-            steps += 1;
-            techniques_used.insert(hint.technique);
+            tracker.record_hint(&hint);
+            
+            if let Some(cap) = cap {
+                let bound = tracker.lower_bound();
+                if bound > cap {
+                    return tracker.finish(bound.clamp(1, 100), false);
+                }
+            }
             
             // Apply hint
             apply_hint(&mut current_grid, &hint);
         } else {
             // Stuck
-            return DifficultyResult { score: 100, solvable: false };
+            let score = if config.score_partial_path { tracker.score() } else { 100 };
+            return tracker.finish(score, false);
         }
     }
 }
 
-fn apply_hint(grid: &mut Grid, hint: &crate::techniques::Hint) {
+/// Lowest estimate `quick_difficulty_estimate` gives a grid the basic
+/// techniques can't finish; anything below it is an exact score.
+pub const STALLED_ESTIMATE_FLOOR: i32 = 40;
+
+/// Cheap proxy for `evaluate_difficulty` when pre-filtering large batches.
+/// Only singles, naked pairs and locked candidates are tried: if they solve the
+/// grid the score is exact (at most ~20), otherwise it is estimated from the
+/// clue count, the singles on offer at the start and the cells left open.
+pub fn quick_difficulty_estimate(grid: &Grid) -> i32 {
+    let clues = grid.clue_count() as i32;
+    let mut current_grid = *grid;
+    crate::solver::update_candidates(&mut current_grid);
+    let singles = (detect_technique_all(&current_grid, "naked_single").len()
+        + detect_technique_all(&current_grid, "hidden_single").len()) as i32;
+
+    let mut tracker = ScoreTracker::new(ScoringConfig::default());
+    loop {
+        tracker.record_singles(propagate_singles_counting(&mut current_grid));
+        if current_grid.is_solved() {
+            return tracker.score();
+        }
+        match get_basic_hint(&current_grid) {
+            Some(hint) => {
+                tracker.record_hint(&hint);
+                apply_hint(&mut current_grid, &hint);
+            }
+            None => break,
+        }
+    }
+
+    // Stalled grids start at the floor and rise with open cells, with few
+    // clues and with a scarcity of opening singles
+    let open = current_grid.values.iter().filter(|&&v| v == 0).count() as i32;
+    let estimate = STALLED_ESTIMATE_FLOOR + open / 4 + (30 - clues).max(0) / 3 + 4 * (3 - singles).max(0);
+    estimate.min(100)
+}
+
+pub fn apply_hint(grid: &mut Grid, hint: &Hint) {
     for &(cell, digit) in &hint.placements {
         grid.set_value(cell, digit);
         update_candidates_after_move(grid, cell, digit);
     }
     for &(cell, digit) in &hint.eliminations {
-        grid.candidates[cell] &= !(1 << (digit - 1));
+        grid.candidates[cell].remove(digit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rating_corpus_keeps_its_categories() {
+        let drifted: Vec<&str> = include_str!("../tests/fixtures/ratings.txt")
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .filter(|l| {
+                let (puzzle, category) = l.split_once(' ').unwrap();
+                !verify_rating(puzzle, category)
+            })
+            .collect();
+        assert!(drifted.is_empty(), "{:#?}", drifted);
+    }
+
+    #[test]
+    fn garbage_grids_are_flagged_not_scored() {
+        let conflict = Grid::from_string("11.............................................................................");
+        let result = evaluate_difficulty(&conflict);
+        assert!(!result.valid && !result.solvable && result.score == 0);
+        assert_eq!(result.category(), INVALID_CATEGORY);
+        let detailed = evaluate_difficulty_detailed(&conflict);
+        assert!(!detailed.valid && detailed.steps == 0 && detailed.guess_depth.is_none());
+        assert_eq!(detailed.category(), INVALID_CATEGORY);
+        assert!(!verify_rating("11", "trivial"));
+
+        let open = Grid::from_string("123456789");
+        let result = evaluate_difficulty_checked(&open);
+        assert!(result.valid);
+        assert_eq!(result.unique, Some(false));
+        assert_eq!(result.score, 0);
+
+        let puzzle = Grid::from_string("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79");
+        let checked = evaluate_difficulty_checked(&puzzle);
+        assert_eq!(checked.unique, Some(true));
+        assert_eq!(checked.score, evaluate_difficulty(&puzzle).score);
+    }
+
+    #[test]
+    fn quick_estimate_orders_the_corpus_like_the_rating() {
+        let rated: Vec<(i32, i32)> = include_str!("../tests/fixtures/ratings.txt")
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .map(|l| {
+                let grid = Grid::from_string(l.split_once(' ').unwrap().0);
+                (evaluate_difficulty(&grid).score, quick_difficulty_estimate(&grid))
+            })
+            .collect();
+        // Exact wherever the basics finish
+        assert!(rated.iter().all(|&(score, quick)| quick >= STALLED_ESTIMATE_FLOOR || quick == score));
+
+        // Pairs ranked the same way by both must far outnumber reversed ones
+        let (mut agree, mut reversed) = (0, 0);
+        for (i, &(a, qa)) in rated.iter().enumerate() {
+            for &(b, qb) in &rated[i + 1..] {
+                if a == b || qa == qb { continue; }
+                if (a < b) == (qa < qb) { agree += 1 } else { reversed += 1 }
+            }
+        }
+        assert!(agree >= 5 * reversed, "{} agree, {} reversed", agree, reversed);
+    }
+
+    #[test]
+    fn search_weight_charges_for_hard_to_spot_steps() {
+        let puzzle = Grid::from_string("4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......");
+        let plain = evaluate_difficulty_detailed(&puzzle);
+        let config = ScoringConfig { search_weight: 1.0, ..ScoringConfig::default() };
+        let weighted = evaluate_difficulty_with_config(&puzzle, &config);
+        assert_eq!(weighted.hardest_technique, plain.hardest_technique);
+        assert!(weighted.score > plain.score, "{} vs {}", weighted.score, plain.score);
+    }
+
+    #[test]
+    fn partial_path_scores_how_far_logic_gets() {
+        let puzzle = Grid::from_string("..53.....8......2..7..1.5..4....53...1..7...6..32...8..6.5....9..4....3......97..");
+        let stalled = evaluate_difficulty_detailed(&puzzle);
+        assert_eq!((stalled.score, stalled.guess_depth), (100, None));
+        assert!(evaluate_with_guess_depth(&puzzle).guess_depth.is_some_and(|depth| depth > 0));
+        let config = ScoringConfig { score_partial_path: true, ..ScoringConfig::default() };
+        let partial = evaluate_difficulty_with_config(&puzzle, &config);
+        assert!(!partial.solvable && partial.steps > 0);
+        assert!(partial.score > 1 && partial.score < 100, "{}", partial.score);
+
+        let easy = Grid::from_string("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79");
+        assert_eq!(evaluate_difficulty_with_config(&easy, &config).score, evaluate_difficulty(&easy).score);
+    }
+
+    #[test]
+    fn full_houses_are_rated_as_full_houses() {
+        let solution = crate::solver::solve(&Grid::from_string("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79")).unwrap();
+        let mut puzzle = solution;
+        puzzle.values[0] = 0;
+        let rating = evaluate_difficulty_detailed(&puzzle);
+        assert_eq!(rating.hardest_technique, "full_house");
+        assert_eq!(rating.techniques, vec![("full_house", 1)]);
     }
 }
@@ -1,191 +1,678 @@
 
-use crate::grid::{Grid, SIZE};
-use crate::solver::{solve, is_unique};
-use crate::difficulty::evaluate_difficulty;
+use crate::grid::{detect_symmetries, Candidates, Grid, SIZE};
+use crate::solver::{solve, solve_randomized, is_unique};
+use crate::difficulty::{
+    evaluate_difficulty, evaluate_difficulty_detailed, quick_difficulty_estimate, DetailedDifficulty, DifficultyResult,
+    STALLED_ESTIMATE_FLOOR,
+};
+use crate::techniques::{technique_difficulty, TechniqueSet};
 use rand::prelude::*;
 use rand::seq::SliceRandom;
 use rand::rngs::SmallRng;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Fresh puzzles `generate_technique_showcase` tries before giving up.
+const SHOWCASE_ATTEMPTS: usize = 200;
+
+/// Fresh puzzles `generate_max_technique` tries to ease under its ceiling.
+const MAX_TECHNIQUE_ATTEMPTS: usize = 200;
+
+/// Searches `generate_asymmetric` runs before giving up.
+const ASYMMETRIC_ATTEMPTS: usize = 8;
+
+// Distinguishes unseeded generators even when the host's entropy is constant.
+static ENTROPY_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// (category, target score, tolerance), easiest first. Shared with
+/// `difficulty::classify` so generation and labeling agree.
+pub const CATEGORIES: &[(&str, i32, i32)] = &[
+    ("trivial", 4, 4),
+    ("basic", 17, 8),
+    ("intermediate", 36, 10),
+    ("tough", 56, 12),
+    ("diabolical", 76, 8),
+    ("extreme", 88, 4),
+    ("master", 94, 2),
+    ("grandmaster", 98, 1),
+];
+
+/// Target score and tolerance for a category; unknown names fall back to "basic".
+fn category_band(category: &str) -> (i32, i32) {
+    CATEGORIES
+        .iter()
+        .find(|(name, _, _)| *name == category)
+        .map(|&(_, target, tolerance)| (target, tolerance))
+        .unwrap_or((17, 8))
+}
+
+/// How the completed solution grid is produced before clues are removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SolutionFill {
+    /// Shuffle the three independent diagonal boxes, then solve. Fast, but the
+    /// deterministic solver biases which completions come out.
+    #[default]
+    DiagonalBoxes,
+    /// Solve the empty grid trying digits in random order at every branch,
+    /// giving a less biased spread of completed grids.
+    RandomizedSolve,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GeneratorConfig {
+    pub solution_fill: SolutionFill,
+    /// When making a puzzle harder, favour cells whose removal raised the
+    /// score earlier in this generator's life. Off by default so seeded
+    /// output (daily puzzles) stays the same. `examples/adaptive_removal.rs`
+    /// measures it: a grandmaster search (never in band, so it runs its full
+    /// budget) ends within a few points as close on a third to two fifths of
+    /// the evaluations, while intermediate, where removals mostly succeed
+    /// anyway, needs more.
+    pub adaptive_removal: bool,
+}
+
+/// A puzzle with the rating the generator already computed for it.
+#[derive(Debug, Clone)]
+pub struct GeneratedPuzzle {
+    pub puzzle: String,
+    pub score: i32,
+    pub hardest_technique: &'static str,
+    pub clue_count: usize,
+}
 
 pub struct Generator {
     rng: SmallRng,
+    config: GeneratorConfig,
+    removal_stats: [(u32, u32); SIZE], // (removals that raised the score, removals rated) per cell
+    evaluations: usize,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Generator {
     pub fn new() -> Self {
-        Generator {
-            rng: SmallRng::from_entropy(),
-        }
+        Self::new_with_entropy_fallback(ENTROPY_COUNTER.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Seeds from host entropy mixed with `counter`. If entropy is unavailable
+    /// (or a WASM host returns constant bytes), distinct counters still give
+    /// distinct streams instead of panicking or repeating puzzles.
+    pub fn new_with_entropy_fallback(counter: u64) -> Self {
+        let mut bytes = [0u8; 8];
+        let entropy = match getrandom::getrandom(&mut bytes) {
+            Ok(()) => u64::from_le_bytes(bytes),
+            Err(_) => 0,
+        };
+        Generator::with_rng(SmallRng::seed_from_u64(entropy ^ counter.wrapping_mul(0x9E37_79B9_7F4A_7C15)), GeneratorConfig::default())
     }
 
     pub fn new_with_seed(seed: u64) -> Self {
-        Generator {
-            rng: SmallRng::seed_from_u64(seed),
-        }
+        Generator::with_rng(SmallRng::seed_from_u64(seed), GeneratorConfig::default())
+    }
+
+    pub fn new_with_config(seed: u64, config: GeneratorConfig) -> Self {
+        Generator::with_rng(SmallRng::seed_from_u64(seed), config)
+    }
+
+    fn with_rng(rng: SmallRng, config: GeneratorConfig) -> Self {
+        Generator { rng, config, removal_stats: [(0, 0); SIZE], evaluations: 0 }
+    }
+
+    /// Difficulty evaluations the hill-climb has run so far, for benchmarking.
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
     }
     
-    pub fn generate(&mut self, category: &str) -> String {
-        let (target, tolerance) = match category {
-            "trivial" => (4, 4),
-            "basic" => (17, 8),
-            "intermediate" => (36, 10),
-            "tough" => (56, 12),
-            "diabolical" => (76, 8),
-            "extreme" => (88, 4),
-            "master" => (94, 2),
-            "grandmaster" => (98, 1),
-            _ => (17, 8),
+    /// Random completed grid, produced according to `config.solution_fill`.
+    pub fn generate_solution(&mut self) -> Option<Grid> {
+        if self.config.solution_fill == SolutionFill::RandomizedSolve {
+            return solve_randomized(&Grid::new(), Some(&mut self.rng));
+        }
+
+        // The three diagonal boxes are independent, so fill them with shuffled
+        // digits and let the solver complete the rest.
+        let mut full_grid = Grid::new();
+        for i in 0..3 {
+            let mut digits: Vec<u8> = (1..=9).collect();
+            digits.shuffle(&mut self.rng);
+            let start_row = i * 3;
+            let start_col = i * 3;
+            for r in 0..3 {
+                for c in 0..3 {
+                    let cell = (start_row + r) * 9 + (start_col + c);
+                    full_grid.set_value(cell, digits[r*3+c]);
+                }
+            }
+        }
+        solve(&full_grid)
+    }
+
+    /// Random completed grid agreeing with `fixed`.
+    fn solution_through(&mut self, fixed: &[(usize, u8)]) -> Option<Grid> {
+        let mut grid = Grid::new();
+        for &(cell, digit) in fixed {
+            grid.set_value(cell, digit);
+        }
+        solve_randomized(&grid, Some(&mut self.rng))
+    }
+
+    /// Puzzle whose givens are exactly the `template` cells. Tries fresh
+    /// solutions until the clue pattern is unique and rates within the
+    /// category band; `None` if none is found within the attempt budget.
+    pub fn generate_from_template(&mut self, template: &[bool; SIZE], category: &str) -> Option<String> {
+        let (target, tolerance) = category_band(category);
+        let max_attempts = 500;
+
+        for _ in 0..max_attempts {
+            let full_grid = match self.generate_solution() {
+                Some(solved) => solved,
+                None => continue,
+            };
+
+            let mut puzzle = Grid::new();
+            for i in (0..SIZE).filter(|&i| template[i]) {
+                puzzle.set_value(i, full_grid.values[i]);
+            }
+
+            if !is_unique(&puzzle) { continue; }
+
+            let score = evaluate_difficulty(&puzzle).score;
+            if (score - target).abs() <= tolerance {
+                return Some(puzzle.to_string());
+            }
+        }
+        None
+    }
+
+    /// Stream of in-band puzzles for `category`, generated lazily on demand.
+    /// Ends at the first search that misses the band instead of retrying
+    /// forever.
+    ///
+    /// ```
+    /// use fast_sudoku::generator::Generator;
+    ///
+    /// let mut gen = Generator::new_with_seed(7);
+    /// let batch: Vec<String> = gen.puzzles("trivial").take(3).collect();
+    /// assert_eq!(batch.len(), 3);
+    /// ```
+    pub fn puzzles<'a>(&'a mut self, category: &'a str) -> impl Iterator<Item = String> + 'a {
+        std::iter::repeat_with(move || self.generate(category)).map_while(|p| p)
+    }
+
+    /// Like `generate`, but guarantees a clue layout with no symmetry at all,
+    /// retrying the rare symmetric result. `None` if `ASYMMETRIC_ATTEMPTS`
+    /// searches all miss the band or come out symmetric.
+    pub fn generate_asymmetric(&mut self, category: &str) -> Option<String> {
+        (0..ASYMMETRIC_ATTEMPTS).find_map(|_| {
+            self.generate(category).filter(|p| detect_symmetries(&Grid::from_string(p)).is_empty())
+        })
+    }
+
+    /// Puzzle rated within the category band. `None` if the search runs out
+    /// of rounds first, or the band is out of the rating model's reach.
+    pub fn generate(&mut self, category: &str) -> Option<String> {
+        self.generate_with_progress(category, |_, _| {})
+    }
+
+    /// `generate` plus the puzzle's score, hardest technique and clue count,
+    /// taken from the search instead of a second evaluation.
+    pub fn generate_rated(&mut self, category: &str) -> Option<GeneratedPuzzle> {
+        let (puzzle, rating, in_band) = self.search(category, None, &[], |_, _| {});
+        if !in_band { return None; }
+        Some(GeneratedPuzzle {
+            puzzle: puzzle.to_string(),
+            score: rating.score,
+            hardest_technique: rating.hardest_technique,
+            clue_count: puzzle.clue_count(),
+        })
+    }
+
+    /// Like `generate`, but the puzzle's solve path must also use
+    /// `required_technique` at least once. `None` for an unknown technique or
+    /// when no such puzzle turns up in the band, e.g. an X-Wing in a trivial one.
+    pub fn generate_requiring(&mut self, category: &str, required_technique: &str) -> Option<String> {
+        if technique_difficulty(required_technique) == 0.0 { return None; }
+        let (puzzle, _, in_band) = self.search(category, Some(required_technique), &[], |_, _| {});
+        if in_band { Some(puzzle.to_string()) } else { None }
+    }
+
+    /// Same as `generate`, calling `progress(round, best_distance)` after each
+    /// round, where `best_distance` is how far the closest puzzle so far is
+    /// from the category target. Meant for native progress bars.
+    pub fn generate_with_progress<F: FnMut(usize, i32)>(&mut self, category: &str, progress: F) -> Option<String> {
+        let (puzzle, _, in_band) = self.search(category, None, &[], progress);
+        if in_band { Some(puzzle.to_string()) } else { None }
+    }
+
+    /// Like `generate`, but every `(cell, digit)` in `fixed` is a given of
+    /// the result, e.g. a date spelled out in the top row. The solution is
+    /// drawn at random among those agreeing with `fixed` (so
+    /// `config.solution_fill` is not used) and the search never removes those
+    /// cells. `None` if `fixed` is out of range, contradicts itself or has no
+    /// completion, or if no in-band puzzle turns up.
+    pub fn generate_with_constraint(&mut self, category: &str, fixed: &[(usize, u8)]) -> Option<String> {
+        let mut givens = Grid::new();
+        for &(cell, digit) in fixed {
+            if cell >= SIZE || !(1..=9).contains(&digit) { return None; }
+            givens.set_value(cell, digit);
+        }
+        // A cell listed twice with different digits can't honour both
+        if fixed.iter().any(|&(cell, digit)| givens.values[cell] != digit) { return None; }
+        solve(&givens)?;
+        let (puzzle, _, in_band) = self.search(category, None, fixed, |_, _| {});
+        if in_band { Some(puzzle.to_string()) } else { None }
+    }
+
+    /// Puzzle that never needs a technique harder than `max_technique` (any
+    /// name from `TECHNIQUES`): clues are added back from the solution until the
+    /// hardest step fits, a categorical rather than score-band guarantee.
+    /// `None` for an unknown technique name, or if `MAX_TECHNIQUE_ATTEMPTS`
+    /// fresh puzzles can't be eased that far.
+    pub fn generate_max_technique(&mut self, max_technique: &str) -> Option<String> {
+        let ceiling = technique_difficulty(max_technique);
+        if ceiling == 0.0 { return None; }
+
+        (0..MAX_TECHNIQUE_ATTEMPTS).find_map(|_| self.ease_to_ceiling(ceiling)).map(|(puzzle, _)| puzzle.to_string())
+    }
+
+    /// Worked example for one technique: a puzzle whose hardest step is
+    /// `technique_name`, preferably with nothing but singles besides it.
+    /// Candidates come from easing fresh puzzles down to the technique's
+    /// difficulty, as in `generate_max_technique`; if `SHOWCASE_ATTEMPTS` give
+    /// no clean example, the first one with the right hardest step is used.
+    /// `None` for an unknown name or when no attempt peaks at the technique.
+    pub fn generate_technique_showcase(&mut self, technique_name: &str) -> Option<String> {
+        let ceiling = technique_difficulty(technique_name);
+        let flag = TechniqueSet::from_technique(technique_name)?;
+        let allowed = TechniqueSet::SINGLES | flag;
+        let mut fallback = None;
+
+        for _ in 0..SHOWCASE_ATTEMPTS {
+            let Some((puzzle, rating)) = self.ease_to_ceiling(ceiling) else { continue };
+            if rating.hardest_technique != technique_name { continue; }
+            let clean = rating.techniques.iter().all(|&(name, _)| TechniqueSet::from_technique(name).is_some_and(|t| allowed.contains(t)));
+            if clean {
+                return Some(puzzle.to_string());
+            }
+            fallback.get_or_insert(puzzle);
+        }
+        fallback.map(|puzzle| puzzle.to_string())
+    }
+
+    /// Strips a fresh solution, then adds clues back until the hardest step
+    /// is no harder than `ceiling`. `None` if even the full grid isn't rated
+    /// that low, which can only happen for a ceiling below the singles.
+    fn ease_to_ceiling(&mut self, ceiling: f32) -> Option<(Grid, DetailedDifficulty)> {
+        let full_grid = self.generate_solution()?;
+        let mut puzzle = self.strip_clues(&full_grid);
+        let mut holes: Vec<usize> = (0..SIZE).filter(|&i| puzzle.values[i] == 0).collect();
+        holes.shuffle(&mut self.rng);
+
+        loop {
+            let rating = evaluate_difficulty_detailed(&puzzle);
+            if rating.solvable && technique_difficulty(rating.hardest_technique) <= ceiling {
+                return Some((puzzle, rating));
+            }
+            let cell = holes.pop()?;
+            puzzle.set_value(cell, full_grid.values[cell]);
+        }
+    }
+
+    /// Random minimal puzzle for `solution`: every clue is tried once, in
+    /// random order, and dropped if the solution stays unique. Removing clues
+    /// never makes another one removable, so a single pass is minimal. Many
+    /// calls on one solution sample different puzzles sharing it.
+    ///
+    /// Panics if `solution` is not a complete, valid grid.
+    pub fn minimal_from_solution(&mut self, solution: &Grid) -> Grid {
+        assert!(solution.is_solved_correctly(), "not a solution grid");
+        let stripped = self.strip_clues_to(solution, 0, &[false; SIZE]);
+        Grid::from_values(&stripped.values).unwrap()
+    }
+
+    /// Removes clues from a solved grid in random order, keeping the solution
+    /// unique, until about 24 remain.
+    fn strip_clues(&mut self, full_grid: &Grid) -> Grid {
+        self.strip_clues_to(full_grid, 24, &[false; SIZE])
+    }
+
+    /// Strips clues in random order down to `target_clues`, never touching the
+    /// `locked` cells.
+    fn strip_clues_to(&mut self, full_grid: &Grid, target_clues: usize, locked: &[bool; SIZE]) -> Grid {
+        let mut current_grid = *full_grid;
+        let mut clues: Vec<usize> = (0..SIZE).collect();
+        clues.shuffle(&mut self.rng);
+        
+        let mut current_clues = SIZE;
+        
+        for &cell in &clues {
+            if current_clues <= target_clues { break; }
+            if locked[cell] { continue; }
+            let val = current_grid.values[cell];
+            current_grid.set_value(cell, 0); // Remove
+            
+            // Optimized uniqueness check
+            if !crate::solver::check_uniqueness_after_removal(&current_grid, cell, val) {
+                current_grid.set_value(cell, val); // Restore
+            } else {
+                current_clues -= 1;
+            }
+        }
+        current_grid
+    }
+
+    /// Moves one clue of `grid` (a unique puzzle for `full`) other than the
+    /// `locked` ones, returning the result if still unique. Picks a direction at random, since either order
+    /// alone skews the clue patterns reached:
+    /// - add then remove: a random clue joins, then a random other one leaves;
+    /// - remove then add: a random clue leaves even if that breaks uniqueness,
+    ///   and the added clue is one where a second solution disagrees with `full`.
+    fn swap_clue(&mut self, grid: &Grid, full: &Grid, locked: &[bool; SIZE]) -> Option<Grid> {
+        let mut next_grid = *grid;
+        let holes: Vec<usize> = (0..SIZE).filter(|&i| grid.values[i] == 0).collect();
+        let clues: Vec<usize> = (0..SIZE).filter(|&i| grid.values[i] != 0 && !locked[i]).collect();
+
+        if self.rng.gen_bool(0.5) {
+            let &add_cell = holes.choose(&mut self.rng)?;
+            let &rem_cell = clues.choose(&mut self.rng)?;
+            next_grid.set_value(add_cell, full.values[add_cell]);
+            next_grid.set_value(rem_cell, 0);
+            return crate::solver::check_uniqueness_after_removal(&next_grid, rem_cell, full.values[rem_cell]).then_some(next_grid);
+        }
+
+        let &rem_cell = clues.choose(&mut self.rng)?;
+        next_grid.set_value(rem_cell, 0);
+        let witness = crate::solver::other_solution(&next_grid, rem_cell, full.values[rem_cell]);
+        let fixes: Vec<usize> = holes
+            .into_iter()
+            .filter(|&i| witness.is_none_or(|w| w.values[i] != full.values[i]))
+            .collect();
+        let &add_cell = fixes.choose(&mut self.rng)?;
+        next_grid.set_value(add_cell, full.values[add_cell]);
+        (witness.is_none() || is_unique(&next_grid)).then_some(next_grid)
+    }
+
+    /// Hill-climbs towards the category target. Returns the puzzle, its rating
+    /// and whether it is in the band (and uses `required`, if given); if not, it
+    /// is the closest one found. The `fixed` givens are kept throughout.
+    fn search<F: FnMut(usize, i32)>(&mut self, category: &str, required: Option<&str>, fixed: &[(usize, u8)], mut progress: F) -> (Grid, DifficultyResult, bool) {
+        let (target, tolerance) = category_band(category);
+        let uses_required = |grid: &Grid| {
+            required.is_none_or(|t| evaluate_difficulty_detailed(grid).techniques.iter().any(|&(name, _)| name == t))
         };
         
+        let mut locked = [false; SIZE];
+        for &(cell, _) in fixed {
+            locked[cell] = true;
+        }
+        
         let max_attempts = 2000; 
         let mut best_puzzle = Grid::new();
+        let mut best_rating = DifficultyResult { score: 0, solvable: false, hardest_technique: "", valid: true, unique: None };
         let mut best_diff_diff = 100;
-        let mut evaluations = 0;
         
-        for _round in 0..max_attempts/100 { // Rounds
+        for round in 0..max_attempts/100 { // Rounds
             // Generate full grid
-            let mut full_grid = Grid::new();
-            // Randomly fill diagonal boxes
-            for i in 0..3 {
-                let _box_idx = i * 4; 
-                let mut digits: Vec<u8> = (1..=9).collect();
-                digits.shuffle(&mut self.rng);
-                let start_row = i * 3;
-                let start_col = i * 3;
-                for r in 0..3 {
-                    for c in 0..3 {
-                        let cell = (start_row + r) * 9 + (start_col + c);
-                        full_grid.set_value(cell, digits[r*3+c]);
-                    }
-                }
-            }
-            
-            if let Some(solved) = solve(&full_grid) {
-                full_grid = solved;
-            } else {
-                continue;
-            }
+            let solution = if fixed.is_empty() { self.generate_solution() } else { self.solution_through(fixed) };
+            let full_grid = match solution {
+                Some(solved) => solved,
+                None => continue,
+            };
             
             // Remove clues to reach start state
-            let mut current_grid = full_grid;
-            let mut clues: Vec<usize> = (0..SIZE).collect();
-            clues.shuffle(&mut self.rng);
-            
-            // Remove until ~24 clues
-            let target_clues = 24;
-            let mut current_clues = SIZE;
-            
-            for &cell in &clues {
-                if current_clues <= target_clues { break; }
-                let val = current_grid.values[cell];
-                current_grid.set_value(cell, 0); // Remove
-                
-                // Optimized uniqueness check
-                if !crate::solver::check_uniqueness_after_removal(&current_grid, cell, val) {
-                    current_grid.set_value(cell, val); // Restore
-                } else {
-                    current_clues -= 1;
-                }
-            }
+            let mut current_grid = self.strip_clues_to(&full_grid, 24, &locked);
             
             // Annealing / Hill Climbing
-            let mut current_diff = evaluate_difficulty(&current_grid).score;
+            let mut current_rating = evaluate_difficulty(&current_grid);
 
             for _step in 0..50 {
-                let diff = current_diff - target;
-                if diff.abs() <= tolerance {
-                    // println!("Found target! Rounds: {}, Evals: {}", _round, evaluations);
-                    return current_grid.to_string();
+                let diff = current_rating.score - target;
+                if diff.abs() <= tolerance && uses_required(&current_grid) {
+                    // println!("Found target! Rounds: {}, Evals: {}", round, evaluations);
+                    return (current_grid, current_rating, true);
                 }
 
                 if diff.abs() < best_diff_diff {
                     best_diff_diff = diff.abs();
                     best_puzzle = current_grid;
+                    best_rating = current_rating;
                 }
 
-                let mut improved = false;
                 let mut attempts = 0;
                 
                 // First Improvement Strategy
                 while attempts < 20 {
                     attempts += 1;
                     let mut next_grid = current_grid;
+                    let mut removed = None;
                     
                     if diff > 0 {
                         // Too hard -> Add clue (make easier)
-                        let mut holes = Vec::new();
-                        for i in 0..SIZE {
-                            if next_grid.values[i] == 0 {
-                                holes.push(i);
-                            }
-                        }
+                        let holes: Vec<usize> = (0..SIZE).filter(|&i| next_grid.values[i] == 0).collect();
                         if let Some(&idx) = holes.choose(&mut self.rng) {
                             next_grid.values[idx] = full_grid.values[idx]; // Use solution value
-                            next_grid.candidates[idx] = 0;
+                            next_grid.candidates[idx] = Candidates::NONE;
                         }
                     } else {
                         // Too easy -> Remove clue (make harder)
-                        let mut clues = Vec::new();
-                        for i in 0..SIZE {
-                            if next_grid.values[i] != 0 {
-                                clues.push(i);
-                            }
-                        }
-                        if let Some(&idx) = clues.choose(&mut self.rng) {
+                        let clues: Vec<usize> = (0..SIZE).filter(|&i| next_grid.values[i] != 0 && !locked[i]).collect();
+                        let picked = if self.config.adaptive_removal {
+                            // Laplace-smoothed success rate, so untried cells stay in play
+                            let stats = &self.removal_stats;
+                            clues.choose_weighted(&mut self.rng, |&c| (stats[c].0 + 1) as f32 / (stats[c].1 + 2) as f32).ok()
+                        } else {
+                            clues.choose(&mut self.rng)
+                        };
+                        if let Some(&idx) = picked {
                             let val = next_grid.values[idx];
                             next_grid.values[idx] = 0;
                             
                             if !crate::solver::check_uniqueness_after_removal(&next_grid, idx, val) {
                                 continue;
                             }
+                            removed = Some(idx);
                         }
                     }
                     
-                    let next_diff = evaluate_difficulty(&next_grid).score;
-                    evaluations += 1;
+                    // Pre-filter: when the basics settle the grid, the quick
+                    // estimate is exact and the full rating only runs on a gain
+                    let quick = quick_difficulty_estimate(&next_grid);
+                    let next_score = if quick < STALLED_ESTIMATE_FLOOR && (quick - target).abs() >= diff.abs() {
+                        None
+                    } else {
+                        self.evaluations += 1;
+                        Some(evaluate_difficulty(&next_grid))
+                    };
+                    if let Some(idx) = removed {
+                        self.removal_stats[idx].1 += 1;
+                        if next_score.as_ref().map_or(quick, |r| r.score) > current_rating.score {
+                            self.removal_stats[idx].0 += 1;
+                        }
+                    }
                     
-                    if (next_diff - target).abs() < diff.abs() {
+                    if let Some(next_rating) = next_score.filter(|r| (r.score - target).abs() < diff.abs()) {
                         current_grid = next_grid;
-                        current_diff = next_diff;
-                        improved = true;
+                        current_rating = next_rating;
                         break; // First improvement found
                     }
                     
                     // Swap Strategy (Escape Local Minima)
-                    if !improved && attempts > 10 {
-                        // Try swapping: Add a random clue, then remove a random clue
-                        let mut holes = Vec::new();
-                        for i in 0..SIZE { if current_grid.values[i] == 0 { holes.push(i); } }
-                        
-                        if let Some(&add_cell) = holes.choose(&mut self.rng) {
-                            let add_val = full_grid.values[add_cell];
-                            next_grid.set_value(add_cell, add_val);
-                            
-                            let mut clues = Vec::new();
-                            for i in 0..SIZE { if next_grid.values[i] != 0 && i != add_cell { clues.push(i); } }
-                            
-                            if let Some(&rem_cell) = clues.choose(&mut self.rng) {
-                                let rem_val = next_grid.values[rem_cell];
-                                next_grid.set_value(rem_cell, 0);
-                                
-                                if crate::solver::check_uniqueness_after_removal(&next_grid, rem_cell, rem_val) {
-                                    let d = evaluate_difficulty(&next_grid).score;
-                                    evaluations += 1;
-                                    // Accept swap if it helps or just to change state
-                                    if (d - target).abs() <= diff.abs() + 2 { // Allow slight degradation
-                                        current_grid = next_grid;
-                                        current_diff = d;
-                                        improved = true;
-                                        break;
-                                    }
-                                }
+                    if attempts > 10 {
+                        if let Some(swapped) = self.swap_clue(&current_grid, &full_grid, &locked) {
+                            let swap_rating = evaluate_difficulty(&swapped);
+                            self.evaluations += 1;
+                            // Accept swap if it helps or just to change state, never if it degrades
+                            if (swap_rating.score - target).abs() <= diff.abs() {
+                                current_grid = swapped;
+                                current_rating = swap_rating;
+                                break;
                             }
                         }
                     }
                 }
             }
+            progress(round, best_diff_diff);
         }
         
         // println!("Finished max rounds. Best diff: {}", best_diff_diff);
-        best_puzzle.to_string()
+        (best_puzzle, best_rating, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_technique_is_a_hard_ceiling() {
+        let mut gen = Generator::new_with_seed(3);
+        for ceiling in ["hidden_single", "pointing_pairs"] {
+            let puzzle = gen.generate_max_technique(ceiling).unwrap();
+            let rating = evaluate_difficulty_detailed(&Grid::from_string(&puzzle));
+            assert!(rating.solvable);
+            assert!(technique_difficulty(rating.hardest_technique) <= technique_difficulty(ceiling), "{}", rating.hardest_technique);
+        }
+        assert!(gen.generate_max_technique("no_such_technique").is_none());
+    }
+
+    #[test]
+    fn rated_puzzle_matches_fresh_evaluation() {
+        let rated = Generator::new_with_seed(2).generate_rated("basic").unwrap();
+        let grid = Grid::from_string(&rated.puzzle);
+        let rating = evaluate_difficulty_detailed(&grid);
+        assert_eq!(rated.score, rating.score);
+        assert_eq!(rated.hardest_technique, rating.hardest_technique);
+        assert_eq!(rated.clue_count, grid.clue_count());
+    }
+
+    #[test]
+    fn adaptive_removal_keeps_puzzles_in_band() {
+        let config = GeneratorConfig { adaptive_removal: true, ..GeneratorConfig::default() };
+        let mut gen = Generator::new_with_config(5, config);
+        for _ in 0..3 {
+            let rated = gen.generate_rated("tough").unwrap();
+            assert!((rated.score - 56).abs() <= 12, "{}", rated.score);
+            assert!(is_unique(&Grid::from_string(&rated.puzzle)));
+        }
+    }
+
+    #[test]
+    fn minimal_puzzles_share_the_given_solution() {
+        let mut gen = Generator::new_with_seed(4);
+        let solution = gen.generate_solution().unwrap();
+        let first = gen.minimal_from_solution(&solution);
+        let second = gen.minimal_from_solution(&solution);
+        assert_ne!(first.values, second.values);
+        for puzzle in [first, second] {
+            assert!(puzzle.is_minimal());
+            assert_eq!(solve(&puzzle).unwrap().values, solution.values);
+        }
+    }
+
+    #[test]
+    fn showcase_peaks_at_the_requested_technique() {
+        let mut gen = Generator::new_with_seed(1);
+        let puzzle = gen.generate_technique_showcase("naked_pairs").unwrap();
+        let rating = evaluate_difficulty_detailed(&Grid::from_string(&puzzle));
+        assert!(rating.solvable);
+        assert_eq!(rating.hardest_technique, "naked_pairs");
+        assert!(gen.generate_technique_showcase("no_such_technique").is_none());
+    }
+
+    #[test]
+    fn generated_scores_stay_in_band_and_ordered() {
+        let mut previous_max = i32::MIN;
+        for (index, (category, target, tolerance)) in CATEGORIES.iter().copied().enumerate() {
+            // Bands past the rating model's reach must fail, not drift out of band
+            let scores: Vec<i32> = (0..2u64)
+                .filter_map(|seed| Generator::new_with_seed(seed).generate(category))
+                .map(|puzzle| evaluate_difficulty(&Grid::from_string(&puzzle)).score)
+                .collect();
+            if scores.is_empty() {
+                assert!(index >= 4, "{}", category);
+                continue;
+            }
+            assert!(scores.iter().all(|s| (s - target).abs() <= tolerance), "{} {:?}", category, scores);
+
+            // Adjacent bands may only overlap within tolerance
+            let min = *scores.iter().min().unwrap();
+            assert!(previous_max <= min + tolerance, "{} {:?}", category, scores);
+            previous_max = *scores.iter().max().unwrap();
+        }
+    }
+
+    #[test]
+    fn asymmetric_puzzles_have_no_symmetry() {
+        for seed in 0..4 {
+            let puzzle = Generator::new_with_seed(seed).generate_asymmetric("basic").unwrap();
+            assert!(detect_symmetries(&Grid::from_string(&puzzle)).is_empty(), "{}", puzzle);
+        }
+    }
+
+    #[test]
+    fn required_technique_appears_in_solve_path() {
+        let mut gen = Generator::new_with_seed(1);
+        let puzzle = gen.generate_requiring("intermediate", "naked_pairs").unwrap();
+        let rating = evaluate_difficulty_detailed(&Grid::from_string(&puzzle));
+        assert!(rating.techniques.iter().any(|&(name, _)| name == "naked_pairs"));
+        assert!((rating.score - 36).abs() <= 10);
+        assert!(gen.generate_requiring("intermediate", "no_such_technique").is_none());
+    }
+
+    #[test]
+    fn clue_swaps_keep_the_puzzle_unique() {
+        let mut gen = Generator::new_with_seed(5);
+        let full = gen.generate_solution().unwrap();
+        let puzzle = gen.strip_clues(&full);
+        let mut swapped = 0;
+        for _ in 0..40 {
+            if let Some(next) = gen.swap_clue(&puzzle, &full, &[false; SIZE]) {
+                assert!(is_unique(&next));
+                assert_eq!(next.clue_count(), puzzle.clue_count());
+                assert_eq!(solve(&next).unwrap().values, full.values);
+                swapped += 1;
+            }
+        }
+        assert!(swapped > 0);
+    }
+
+    #[test]
+    fn same_band_puzzles_spread_their_clues() {
+        // Mean Jaccard distance between clue layouts; independent random
+        // layouts of this density sit near 0.8
+        let mut gen = Generator::new_with_seed(6);
+        let layouts: Vec<[bool; SIZE]> = (0..4)
+            .map(|_| Grid::from_string(&gen.generate("intermediate").unwrap()).values.map(|v| v != 0))
+            .collect();
+        let mut total = 0.0;
+        let mut pairs = 0;
+        for i in 0..layouts.len() {
+            for j in i + 1..layouts.len() {
+                let shared = (0..SIZE).filter(|&c| layouts[i][c] && layouts[j][c]).count();
+                let either = (0..SIZE).filter(|&c| layouts[i][c] || layouts[j][c]).count();
+                total += 1.0 - shared as f32 / either as f32;
+                pairs += 1;
+            }
+        }
+        let diversity = total / pairs as f32;
+        assert!(diversity > 0.7, "{}", diversity);
+    }
+
+    #[test]
+    fn constrained_puzzles_keep_the_fixed_givens() {
+        let fixed = [(0, 1), (1, 6), (2, 9), (40, 5)];
+        let puzzle = Generator::new_with_seed(7).generate_with_constraint("intermediate", &fixed).unwrap();
+        let grid = Grid::from_string(&puzzle);
+        assert!(fixed.iter().all(|&(cell, digit)| grid.values[cell] == digit));
+        assert!(is_unique(&grid));
+        assert!((evaluate_difficulty(&grid).score - 36).abs() <= 10);
+
+        let mut gen = Generator::new_with_seed(7);
+        assert!(gen.generate_with_constraint("intermediate", &[(0, 5), (8, 5)]).is_none());
+        assert!(gen.generate_with_constraint("intermediate", &[(0, 5), (0, 6)]).is_none());
+        assert!(gen.generate_with_constraint("intermediate", &[(81, 1)]).is_none());
     }
 }
@@ -1,17 +1,170 @@
 
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not};
+use crate::utils::{cells_seeing, get_peers, BOXES, COLS, ROWS};
+
 pub const SIZE: usize = 81;
+/// Length of `Grid::to_bytes`: 4 bits per cell.
+pub const PACKED_SIZE: usize = SIZE.div_ceil(2);
+
+/// Set of candidate digits for a cell: bit `d - 1` is set when `d` is possible.
+/// The operators act as set union/intersection/complement over 1..=9.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Candidates(pub u16);
+
+impl Candidates {
+    pub const NONE: Candidates = Candidates(0);
+    pub const ALL: Candidates = Candidates(0x1FF);
+
+    pub fn from_digit(digit: u8) -> Self {
+        Candidates(1 << (digit - 1))
+    }
+
+    pub fn contains(self, digit: u8) -> bool {
+        (self.0 >> (digit - 1)) & 1 == 1
+    }
+
+    pub fn insert(&mut self, digit: u8) {
+        self.0 |= 1 << (digit - 1);
+    }
+
+    pub fn remove(&mut self, digit: u8) {
+        self.0 &= !(1 << (digit - 1));
+    }
+
+    pub fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Lowest digit in the set.
+    pub fn first(self) -> Option<u8> {
+        if self.0 == 0 { None } else { Some(self.0.trailing_zeros() as u8 + 1) }
+    }
+
+    /// The digit, if exactly one remains.
+    pub fn single(self) -> Option<u8> {
+        if self.len() == 1 { self.first() } else { None }
+    }
+
+    /// Digits in ascending order.
+    pub fn iter(self) -> impl Iterator<Item = u8> {
+        (1..=9u8).filter(move |&d| self.contains(d))
+    }
+}
+
+impl BitOr for Candidates {
+    type Output = Candidates;
+    fn bitor(self, rhs: Candidates) -> Candidates {
+        Candidates(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for Candidates {
+    type Output = Candidates;
+    fn bitand(self, rhs: Candidates) -> Candidates {
+        Candidates(self.0 & rhs.0)
+    }
+}
+
+impl BitOrAssign for Candidates {
+    fn bitor_assign(&mut self, rhs: Candidates) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAndAssign for Candidates {
+    fn bitand_assign(&mut self, rhs: Candidates) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl Not for Candidates {
+    type Output = Candidates;
+    fn not(self) -> Candidates {
+        Candidates(!self.0 & 0x1FF)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceError {
+    InvalidDigit,
+    CellFilled,
+    NotACandidate,
+    Given,
+    Contradiction { peer: usize }, // Peer left with no candidates
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    WrongLength(usize),
+    InvalidChar { index: usize, ch: char },
+    InvalidValue { index: usize, value: u8 }, // Raw byte outside 0..=9
+    InvalidMask { index: usize, mask: u16 },  // Bits set above digit 9
+    UnclosedBrace { index: usize },           // `{` at char `index` never closed
+}
+
+/// Clue-pattern symmetries checked by `Grid::has_symmetry`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    Rotational180,
+    Rotational90,
+    MirrorHorizontal, // Top half mirrors bottom half
+    MirrorVertical,   // Left half mirrors right half
+    Diagonal,         // Main diagonal, top-left to bottom-right
+    AntiDiagonal,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 6] = [
+        Symmetry::Rotational180,
+        Symmetry::Rotational90,
+        Symmetry::MirrorHorizontal,
+        Symmetry::MirrorVertical,
+        Symmetry::Diagonal,
+        Symmetry::AntiDiagonal,
+    ];
+
+    /// snake_case name, as used in JSON output.
+    pub fn name(self) -> &'static str {
+        match self {
+            Symmetry::Rotational180 => "rotational_180",
+            Symmetry::Rotational90 => "rotational_90",
+            Symmetry::MirrorHorizontal => "mirror_horizontal",
+            Symmetry::MirrorVertical => "mirror_vertical",
+            Symmetry::Diagonal => "diagonal",
+            Symmetry::AntiDiagonal => "anti_diagonal",
+        }
+    }
+}
+
+/// Every symmetry the clue pattern has, in `Symmetry::ALL` order. Empty for
+/// a scattered layout.
+pub fn detect_symmetries(grid: &Grid) -> Vec<Symmetry> {
+    Symmetry::ALL.iter().copied().filter(|&s| grid.has_symmetry(s)).collect()
+}
 
+/// Board state. `values` is the source of truth: filled cells hold no
+/// candidates and each empty cell's mask is a subset of the digits its peers
+/// leave open. `solver::update_candidates` (which starts with
+/// `reset_candidates`) rebuilds that state from `values` alone, dropping any
+/// eliminations.
 #[derive(Clone, Copy, Debug)]
 pub struct Grid {
     pub values: [u8; SIZE],
-    pub candidates: [u16; SIZE],
+    pub candidates: [Candidates; SIZE], // Only meaningful where values[cell] == 0
+    pub givens: [bool; SIZE], // Original clues, set when parsing
 }
 
 impl Grid {
     pub fn new() -> Self {
         Grid {
             values: [0; SIZE],
-            candidates: [0x1FF; SIZE], // All 9 bits set
+            candidates: [Candidates::ALL; SIZE],
+            givens: [false; SIZE],
         }
     }
 
@@ -22,32 +175,796 @@ impl Grid {
             if let Some(d) = c.to_digit(10) {
                 if d > 0 {
                     grid.set_value(i, d as u8);
+                    grid.givens[i] = true;
                 }
             }
         }
         grid
     }
 
-    pub fn to_string(&self) -> String {
-        let mut s = String::with_capacity(SIZE);
-        for v in self.values.iter() {
-            if *v == 0 {
-                s.push('.');
-            } else {
-                s.push(std::char::from_digit(*v as u32, 10).unwrap());
+    /// `from_string` into an existing grid: values (and givens) are replaced
+    /// and candidates recomputed, so the grid is consistent afterwards.
+    pub fn load_values(&mut self, s: &str) {
+        self.values = [0; SIZE];
+        self.givens = [false; SIZE];
+        for (i, c) in s.chars().take(SIZE).enumerate() {
+            if let Some(d @ 1..=9) = c.to_digit(10) {
+                self.values[i] = d as u8;
+                self.givens[i] = true;
             }
         }
-        s
+        crate::solver::update_candidates(self);
+    }
+
+    /// Strict counterpart of `from_string` for machine-generated data: exactly
+    /// 81 chars, each `0`-`9` (`0` = blank). Anything else is an error.
+    pub fn from_string_strict(s: &str) -> Result<Self, ParseError> {
+        let len = s.chars().count();
+        if len != SIZE {
+            return Err(ParseError::WrongLength(len));
+        }
+        let mut grid = Grid::new();
+        for (i, c) in s.chars().enumerate() {
+            match c.to_digit(10) {
+                Some(0) => {}
+                Some(d) => {
+                    grid.set_value(i, d as u8);
+                    grid.givens[i] = true;
+                }
+                None => return Err(ParseError::InvalidChar { index: i, ch: c }),
+            }
+        }
+        Ok(grid)
+    }
+
+    /// Native-side constructor from raw digits (`0` = blank), skipping string
+    /// parsing. Nonzero cells become givens and candidates are computed.
+    pub fn from_values(vals: &[u8; SIZE]) -> Result<Self, ParseError> {
+        let mut grid = Grid::new();
+        for (i, &v) in vals.iter().enumerate() {
+            match v {
+                0 => {}
+                1..=9 => {
+                    grid.set_value(i, v);
+                    grid.givens[i] = true;
+                }
+                _ => return Err(ParseError::InvalidValue { index: i, value: v }),
+            }
+        }
+        crate::solver::update_candidates(&mut grid);
+        Ok(grid)
+    }
+
+    /// Number of filled cells.
+    pub fn clue_count(&self) -> usize {
+        self.values.iter().filter(|&&v| v != 0).count()
+    }
+
+    pub fn values_array(&self) -> [u8; SIZE] {
+        self.values
+    }
+
+    /// Values packed two cells per byte, the even cell in the high nibble; the
+    /// last byte's low nibble is padding. Givens and candidates are dropped.
+    pub fn to_bytes(&self) -> [u8; PACKED_SIZE] {
+        let mut bytes = [0u8; PACKED_SIZE];
+        for i in 0..SIZE {
+            bytes[i / 2] |= if i % 2 == 0 { self.values[i] << 4 } else { self.values[i] };
+        }
+        bytes
+    }
+
+    /// Inverse of `to_bytes`, with `from_values` semantics: nonzero cells
+    /// become givens. A nibble above 9 is an `InvalidValue` at its cell.
+    pub fn from_bytes(bytes: &[u8; PACKED_SIZE]) -> Result<Self, ParseError> {
+        let mut vals = [0u8; SIZE];
+        for i in 0..SIZE {
+            vals[i] = if i % 2 == 0 { bytes[i / 2] >> 4 } else { bytes[i / 2] & 0x0F };
+        }
+        Grid::from_values(&vals)
     }
 
     pub fn set_value(&mut self, index: usize, value: u8) {
         self.values[index] = value;
-        self.candidates[index] = 0;
+        self.candidates[index] = Candidates::NONE;
         // Propagate constraints? For now, just set.
         // In a real solver, we would update peers.
     }
     
+    /// Forgets every elimination: empty cells get all nine candidates and
+    /// filled cells none. Peers are not consulted; `solver::update_candidates`
+    /// resets and then applies them.
+    pub fn reset_candidates(&mut self) {
+        for i in 0..SIZE {
+            self.candidates[i] = if self.values[i] == 0 { Candidates::ALL } else { Candidates::NONE };
+        }
+    }
+
+    pub fn is_given(&self, cell: usize) -> bool {
+        self.givens[cell]
+    }
+
+    /// Interactive placement: unlike `set_value`, refuses givens, checks that
+    /// `digit` is still a candidate and removes it from all 20 peers. The grid
+    /// is left untouched on error.
+    pub fn place(&mut self, cell: usize, digit: u8) -> Result<(), PlaceError> {
+        if digit == 0 || digit > 9 {
+            return Err(PlaceError::InvalidDigit);
+        }
+        if self.givens[cell] {
+            return Err(PlaceError::Given);
+        }
+        if self.values[cell] != 0 {
+            return Err(PlaceError::CellFilled);
+        }
+        if !self.candidates[cell].contains(digit) {
+            return Err(PlaceError::NotACandidate);
+        }
+
+        let peers = cells_seeing(cell);
+        for &peer in peers {
+            if self.values[peer] == 0 && self.candidates[peer] == Candidates::from_digit(digit) {
+                return Err(PlaceError::Contradiction { peer });
+            }
+        }
+
+        self.set_value(cell, digit);
+        for &peer in peers {
+            if self.values[peer] == 0 {
+                self.candidates[peer].remove(digit);
+            }
+        }
+        Ok(())
+    }
+
+    /// `place` that also reports what it changed: `(peer, removed_bits)` for
+    /// every peer that lost the digit, ascending by cell, so a UI can redraw
+    /// just those pencil marks. Errors as `place`, leaving the grid untouched.
+    pub fn place_reporting(&mut self, cell: usize, digit: u8) -> Result<Vec<(usize, u16)>, PlaceError> {
+        let before = self.candidates;
+        self.place(cell, digit)?;
+        Ok(cells_seeing(cell)
+            .iter()
+            .map(|&peer| (peer, before[peer].0 & !self.candidates[peer].0))
+            .filter(|&(_, removed)| removed != 0)
+            .collect())
+    }
+
+    /// (cell, old_value, new_value) for every cell whose value differs in `other`.
+    pub fn diff(&self, other: &Grid) -> Vec<(usize, u8, u8)> {
+        (0..SIZE)
+            .filter(|&i| self.values[i] != other.values[i])
+            .map(|i| (i, self.values[i], other.values[i]))
+            .collect()
+    }
+
+    /// (cell, old_mask, new_mask) for every cell whose candidates differ in `other`.
+    pub fn candidate_diff(&self, other: &Grid) -> Vec<(usize, Candidates, Candidates)> {
+        (0..SIZE)
+            .filter(|&i| self.candidates[i] != other.candidates[i])
+            .map(|i| (i, self.candidates[i], other.candidates[i]))
+            .collect()
+    }
+
+    /// Checks that every empty cell's mask is exactly the digits absent from its
+    /// peers, i.e. the state `update_candidates` would produce. Returns the first
+    /// offending cell. Grids with technique eliminations applied will fail this.
+    pub fn assert_candidates_consistent(&self) -> Result<(), usize> {
+        for cell in 0..SIZE {
+            if self.values[cell] != 0 { continue; }
+            let mut expected = Candidates::ALL;
+            for peer in get_peers(cell) {
+                if self.values[peer] != 0 {
+                    expected.remove(self.values[peer]);
+                }
+            }
+            if self.candidates[cell] != expected {
+                return Err(cell);
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds a grid where each cell takes its value, candidates and given flag
+    /// from `source(cell)` in `self`.
+    fn permute_cells(&self, source: impl Fn(usize, usize) -> usize) -> Grid {
+        let mut grid = *self;
+        for r in 0..9 {
+            for c in 0..9 {
+                let src = source(r, c);
+                grid.values[r * 9 + c] = self.values[src];
+                grid.candidates[r * 9 + c] = self.candidates[src];
+                grid.givens[r * 9 + c] = self.givens[src];
+            }
+        }
+        grid
+    }
+
+    /// Rotates the grid 90° clockwise.
+    pub fn rotate90(&self) -> Grid {
+        self.permute_cells(|r, c| (8 - c) * 9 + r)
+    }
+
+    /// Mirrors the grid across the main diagonal.
+    pub fn transpose(&self) -> Grid {
+        self.permute_cells(|r, c| c * 9 + r)
+    }
+
+    /// Renames digits: `d` becomes `map[d - 1]`. `map` must be a permutation of 1..=9.
+    pub fn relabel(&self, map: [u8; 9]) -> Grid {
+        let mut grid = *self;
+        for i in 0..SIZE {
+            if self.values[i] != 0 {
+                grid.values[i] = map[self.values[i] as usize - 1];
+            }
+            let mut mask = Candidates::NONE;
+            for d in self.candidates[i].iter() {
+                mask.insert(map[d as usize - 1]);
+            }
+            grid.candidates[i] = mask;
+        }
+        grid
+    }
+
+    /// Swaps two bands (groups of three rows), 0-based.
+    pub fn swap_bands(&self, i: usize, j: usize) -> Grid {
+        self.permute_cells(|r, c| {
+            let band = if r / 3 == i { j } else if r / 3 == j { i } else { r / 3 };
+            (band * 3 + r % 3) * 9 + c
+        })
+    }
+
+    /// Swaps two stacks (groups of three columns), 0-based.
+    pub fn swap_stacks(&self, i: usize, j: usize) -> Grid {
+        self.permute_cells(|r, c| {
+            let stack = if c / 3 == i { j } else if c / 3 == j { i } else { c / 3 };
+            r * 9 + stack * 3 + c % 3
+        })
+    }
+
+    /// Swaps rows `i` and `j` (0-2) inside `band`.
+    pub fn swap_rows_within_band(&self, band: usize, i: usize, j: usize) -> Grid {
+        let (ri, rj) = (band * 3 + i, band * 3 + j);
+        self.permute_cells(|r, c| {
+            let row = if r == ri { rj } else if r == rj { ri } else { r };
+            row * 9 + c
+        })
+    }
+
+    /// Swaps columns `i` and `j` (0-2) inside `stack`.
+    pub fn swap_cols_within_stack(&self, stack: usize, i: usize, j: usize) -> Grid {
+        let (ci, cj) = (stack * 3 + i, stack * 3 + j);
+        self.permute_cells(|r, c| {
+            let col = if c == ci { cj } else if c == cj { ci } else { c };
+            r * 9 + col
+        })
+    }
+
+    /// Digit `cell` takes in the solution (the puzzle is assumed unique), or
+    /// `None` if the cell is already filled or the grid has no solution.
+    pub fn solved_value(&self, cell: usize) -> Option<u8> {
+        if self.values[cell] != 0 {
+            return None;
+        }
+        crate::solver::solve(self).map(|solution| solution.values[cell])
+    }
+
+    /// True if no clue can be removed without losing uniqueness. Assumes the
+    /// puzzle itself has a unique solution.
+    pub fn is_minimal(&self) -> bool {
+        (0..SIZE).filter(|&cell| self.values[cell] != 0).all(|cell| {
+            let mut without = *self;
+            without.values[cell] = 0;
+            !crate::solver::check_uniqueness_after_removal(&without, cell, self.values[cell])
+        })
+    }
+
+    /// True if the clue pattern (which cells are filled, not their digits) maps
+    /// onto itself under `symmetry`.
+    pub fn has_symmetry(&self, symmetry: Symmetry) -> bool {
+        let image = |r: usize, c: usize| match symmetry {
+            Symmetry::Rotational180 => (8 - r) * 9 + (8 - c),
+            Symmetry::Rotational90 => c * 9 + (8 - r),
+            Symmetry::MirrorHorizontal => (8 - r) * 9 + c,
+            Symmetry::MirrorVertical => r * 9 + (8 - c),
+            Symmetry::Diagonal => c * 9 + r,
+            Symmetry::AntiDiagonal => (8 - c) * 9 + (8 - r),
+        };
+        (0..SIZE).all(|cell| (self.values[cell] != 0) == (self.values[image(cell / 9, cell % 9)] != 0))
+    }
+
+    /// True if no two filled cells that see each other hold the same digit.
+    /// Says nothing about whether the puzzle can be completed.
+    pub fn is_valid(&self) -> bool {
+        ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()).all(|unit| {
+            let mut seen = Candidates::NONE;
+            unit.iter().all(|&cell| {
+                let v = self.values[cell];
+                if v == 0 { return true; }
+                let fresh = !seen.contains(v);
+                seen.insert(v);
+                fresh
+            })
+        })
+    }
+
     pub fn is_solved(&self) -> bool {
         self.values.iter().all(|&v| v != 0)
     }
+
+    /// Filled and free of duplicates in every unit, i.e. a real win.
+    /// `is_solved` only checks that no cell is blank.
+    pub fn is_solved_correctly(&self) -> bool {
+        self.is_solved() && self.is_valid()
+    }
+
+    /// Parses a `candidates_string`. A cell with a single candidate is read as
+    /// filled unless a peer still lists that digit, in which case it is an
+    /// unplaced naked single. Filled cells are not marked given: the format
+    /// doesn't record which were clues.
+    pub fn from_candidates_string(s: &str) -> Result<Self, ParseError> {
+        let chars: Vec<char> = s.chars().collect();
+        if chars.len() != SIZE * 9 {
+            return Err(ParseError::WrongLength(chars.len()));
+        }
+
+        let mut grid = Grid::new();
+        for cell in 0..SIZE {
+            let mut mask = Candidates::NONE;
+            for d in 1..=9u8 {
+                let index = cell * 9 + d as usize - 1;
+                match chars[index] {
+                    '.' => {}
+                    ch if ch == (b'0' + d) as char => mask.insert(d),
+                    ch => return Err(ParseError::InvalidChar { index, ch }),
+                }
+            }
+            grid.candidates[cell] = mask;
+        }
+        Ok(grid.promote_singles())
+    }
+
+    /// Parses the extended format some tools export for half-solved grids,
+    /// one of these per cell, in row-major order:
+    ///
+    /// - `1`-`9`: a filled cell, taken as a given;
+    /// - `.` or `0`: an empty cell with no pencil marks recorded, whose
+    ///   candidates are computed from its peers;
+    /// - `{159}`: an empty cell with exactly the listed candidates, kept as
+    ///   written even if a peer rules one out.
+    ///
+    /// Whitespace between cells is ignored. Errors: `WrongLength` with the
+    /// number of cells if not 81, `UnclosedBrace` for a `{` without `}`, and
+    /// `InvalidChar` (char index) for anything else, including a nested `{`,
+    /// a non-digit inside braces and an empty `{}`.
+    pub fn from_extended(s: &str) -> Result<Self, ParseError> {
+        let mut values = Vec::with_capacity(SIZE);
+        let mut marks: Vec<Option<Candidates>> = Vec::with_capacity(SIZE);
+        let mut chars = s.chars().enumerate();
+        while let Some((index, ch)) = chars.next() {
+            match ch {
+                '.' | '0' => {
+                    values.push(0);
+                    marks.push(None);
+                }
+                '1'..='9' => {
+                    values.push(ch as u8 - b'0');
+                    marks.push(None);
+                }
+                '{' => {
+                    let mut mask = Candidates::NONE;
+                    loop {
+                        match chars.next() {
+                            Some((_, d @ '1'..='9')) => mask.insert(d as u8 - b'0'),
+                            Some((close, '}')) if mask.is_empty() => return Err(ParseError::InvalidChar { index: close, ch: '}' }),
+                            Some((_, '}')) => break,
+                            Some((i, other)) => return Err(ParseError::InvalidChar { index: i, ch: other }),
+                            None => return Err(ParseError::UnclosedBrace { index }),
+                        }
+                    }
+                    values.push(0);
+                    marks.push(Some(mask));
+                }
+                ch if ch.is_whitespace() => {}
+                ch => return Err(ParseError::InvalidChar { index, ch }),
+            }
+        }
+        let values: [u8; SIZE] = values.try_into().map_err(|v: Vec<u8>| ParseError::WrongLength(v.len()))?;
+
+        let mut grid = Grid::from_values(&values)?;
+        for (cell, mark) in marks.into_iter().enumerate() {
+            if let Some(mask) = mark {
+                grid.candidates[cell] = mask;
+            }
+        }
+        Ok(grid)
+    }
+
+    /// Raw-mask counterpart of `from_candidates_string`, one `Candidates` bit
+    /// pattern per cell as returned by `candidate_masks`.
+    pub fn from_candidate_masks(masks: &[u16]) -> Result<Self, ParseError> {
+        if masks.len() != SIZE {
+            return Err(ParseError::WrongLength(masks.len()));
+        }
+        let mut grid = Grid::new();
+        for (index, &mask) in masks.iter().enumerate() {
+            if mask & !Candidates::ALL.0 != 0 {
+                return Err(ParseError::InvalidMask { index, mask });
+            }
+            grid.candidates[index] = Candidates(mask);
+        }
+        Ok(grid.promote_singles())
+    }
+
+    /// Fills each lone candidate no peer still lists. The format can't tell
+    /// clues from the player's placements, so none are marked given.
+    fn promote_singles(mut self) -> Self {
+        for cell in 0..SIZE {
+            if let Some(digit) = self.candidates[cell].single() {
+                if get_peers(cell).iter().all(|&peer| !self.candidates[peer].contains(digit)) {
+                    self.values[cell] = digit;
+                }
+            }
+        }
+        for cell in 0..SIZE {
+            if self.values[cell] != 0 {
+                self.candidates[cell] = Candidates::NONE;
+            }
+        }
+        self
+    }
+
+    /// Pencil marks as raw `Candidates` bits, filled cells holding only their
+    /// value's bit like `candidates_string`.
+    pub fn candidate_masks(&self) -> [u16; SIZE] {
+        std::array::from_fn(|i| if self.values[i] != 0 { Candidates::from_digit(self.values[i]).0 } else { self.candidates[i].0 })
+    }
+
+    /// 729-char pencil-mark string: 9 slots per cell, slot `d-1` holds `d` if
+    /// it's a candidate and `.` otherwise. Filled cells show only their value.
+    pub fn candidates_string(&self) -> String {
+        let mut s = String::with_capacity(SIZE * 9);
+        for i in 0..SIZE {
+            let mask = if self.values[i] != 0 { Candidates::from_digit(self.values[i]) } else { self.candidates[i] };
+            for d in 1..=9u8 {
+                if mask.contains(d) {
+                    s.push((b'0' + d) as char);
+                } else {
+                    s.push('.');
+                }
+            }
+        }
+        s
+    }
+
+    /// Minimal-lexicographic representative of this puzzle under the sudoku
+    /// symmetry group (digit relabeling, band/stack swaps, row/column swaps
+    /// within a band/stack, and transposition). Blanks compare as `0`, so two
+    /// puzzles are isomorphic iff their canonical forms are equal. Its filled
+    /// cells are givens and its candidates are computed.
+    pub fn canonical(&self) -> Grid {
+        let mut best = [u8::MAX; SIZE];
+
+        for transpose in [false, true] {
+            for stack_perm in &PERMS3 {
+                for p0 in &PERMS3 {
+                    for p1 in &PERMS3 {
+                        for p2 in &PERMS3 {
+                            let col_perms = [p0, p1, p2];
+                            // Column order: stack_perm picks stacks, col_perms orders columns within each
+                            let col_order: [usize; 9] = std::array::from_fn(|i| stack_perm[i / 3] * 3 + col_perms[i / 3][i % 3]);
+
+                            let permuted: [u8; SIZE] = std::array::from_fn(|i| {
+                                let (r, c) = (i / 9, i % 9);
+                                let src = if transpose {
+                                    col_order[c] * 9 + r
+                                } else {
+                                    r * 9 + col_order[c]
+                                };
+                                self.values[src]
+                            });
+
+                            canonical_rows(&permuted, 0, 0, [0; 10], 1, 0, &mut best);
+                        }
+                    }
+                }
+            }
+        }
+
+        Grid::from_values(&best).expect("relabeled digits stay in 1..=9")
+    }
+
+    pub fn canonical_string(&self) -> String {
+        self.canonical().to_string()
+    }
+}
+
+const PERMS3: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+// Depth-first search over row orderings for a fixed column ordering.
+// `best` always holds the smallest (partial) form found so far; rows below the
+// point where we beat it are reset to u8::MAX so any completion replaces them.
+fn canonical_rows(
+    permuted: &[u8; SIZE],
+    depth: usize,
+    used_rows: u16,
+    labels: [u8; 10],
+    next_label: u8,
+    band: usize,
+    best: &mut [u8; SIZE],
+) {
+    if depth == 9 {
+        return;
+    }
+
+    for r in 0..9 {
+        if (used_rows >> r) & 1 == 1 { continue; }
+        // Rows 0, 3, 6 open a new band; the rest must stay in the current one
+        if depth.is_multiple_of(3) {
+            if (used_rows >> (r / 3 * 3)) & 0b111 != 0 { continue; }
+        } else if r / 3 != band {
+            continue;
+        }
+
+        let mut labels = labels;
+        let mut next_label = next_label;
+        let mut row = [0u8; 9];
+        for c in 0..9 {
+            let v = permuted[r * 9 + c];
+            if v != 0 {
+                if labels[v as usize] == 0 {
+                    labels[v as usize] = next_label;
+                    next_label += 1;
+                }
+                row[c] = labels[v as usize];
+            }
+        }
+
+        let start = depth * 9;
+        match row[..].cmp(&best[start..start + 9]) {
+            std::cmp::Ordering::Greater => continue,
+            std::cmp::Ordering::Less => {
+                best[start..start + 9].copy_from_slice(&row);
+                for v in best[start + 9..].iter_mut() {
+                    *v = u8::MAX;
+                }
+            }
+            std::cmp::Ordering::Equal => {}
+        }
+
+        canonical_rows(permuted, depth + 1, used_rows | (1 << r), labels, next_label, r / 3, best);
+    }
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = String::with_capacity(SIZE);
+        for v in self.values.iter() {
+            if *v == 0 {
+                s.push('.');
+            } else {
+                s.push(std::char::from_digit(*v as u32, 10).unwrap());
+            }
+        }
+        f.write_str(&s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::update_candidates;
+
+    #[test]
+    fn candidates_consistent_after_update() {
+        let mut grid = Grid::from_string("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79");
+        assert_eq!(grid.assert_candidates_consistent(), Err(2));
+        update_candidates(&mut grid);
+        assert_eq!(grid.assert_candidates_consistent(), Ok(()));
+        grid.candidates[2].remove(1);
+        assert_eq!(grid.assert_candidates_consistent(), Err(2));
+    }
+
+    #[test]
+    fn rotate90_four_times_is_identity() {
+        let mut grid = Grid::from_string("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79");
+        update_candidates(&mut grid);
+        let rotated = grid.rotate90();
+        assert_ne!(rotated.values, grid.values);
+        let back = rotated.rotate90().rotate90().rotate90();
+        assert_eq!(back.values, grid.values);
+        assert_eq!(back.candidates, grid.candidates);
+        assert_eq!(back.givens, grid.givens);
+    }
+
+    #[test]
+    fn candidates_string_round_trips() {
+        let mut grid = Grid::from_string("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79");
+        update_candidates(&mut grid);
+        grid.candidates[2].remove(1);
+        let s = grid.candidates_string();
+        let parsed = Grid::from_candidates_string(&s).unwrap();
+        assert_eq!(parsed.candidates_string(), s);
+        assert_eq!(parsed.values[0], 5);
+        assert_eq!(Grid::from_candidates_string("12").err(), Some(ParseError::WrongLength(2)));
+    }
+
+    #[test]
+    fn candidate_masks_round_trip() {
+        let mut grid = Grid::from_string("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79");
+        update_candidates(&mut grid);
+        grid.candidates[2].remove(1);
+        let masks = grid.candidate_masks();
+        assert_eq!(masks[0], 1 << 4);
+        let parsed = Grid::from_candidate_masks(&masks).unwrap();
+        assert!(parsed.values[0] == 5 && !parsed.is_given(0));
+        assert_eq!(parsed.candidates_string(), grid.candidates_string());
+        assert_eq!(Grid::from_candidate_masks(&masks[1..]).err(), Some(ParseError::WrongLength(80)));
+        let mut bad = masks;
+        bad[3] = 0x200;
+        assert_eq!(Grid::from_candidate_masks(&bad).err(), Some(ParseError::InvalidMask { index: 3, mask: 0x200 }));
+    }
+
+    #[test]
+    fn reset_then_update_drops_eliminations() {
+        let mut grid = Grid::from_string("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79");
+        update_candidates(&mut grid);
+        let fresh = grid;
+        grid.candidates[2].remove(1);
+        grid.reset_candidates();
+        assert_eq!(grid.candidates[0], Candidates::NONE);
+        assert_eq!(grid.candidates[2], Candidates::ALL);
+        update_candidates(&mut grid);
+        assert_eq!(grid.candidates_string(), fresh.candidates_string());
+        assert_eq!(grid.assert_candidates_consistent(), Ok(()));
+    }
+
+    #[test]
+    fn seventeen_clue_puzzle_is_minimal() {
+        let grid = Grid::from_string("000000010400000000020000000000050407008000300001090000300400200050100000000806000");
+        assert!(grid.is_minimal());
+        assert!(!grid.has_symmetry(Symmetry::Rotational180));
+
+        let mut padded = grid;
+        padded.set_value(80, crate::solver::solve(&grid).unwrap().values[80]);
+        assert!(!padded.is_minimal());
+    }
+
+    #[test]
+    fn symmetry_follows_clue_pattern() {
+        let mut grid = Grid::new();
+        grid.set_value(1, 3);
+        grid.set_value(79, 7);
+        assert!(grid.has_symmetry(Symmetry::Rotational180));
+        assert!(!grid.has_symmetry(Symmetry::Rotational90));
+        assert!(!grid.has_symmetry(Symmetry::MirrorHorizontal));
+        grid.set_value(17, 1);
+        grid.set_value(63, 2);
+        assert!(grid.has_symmetry(Symmetry::Rotational90));
+        assert_eq!(detect_symmetries(&grid), vec![Symmetry::Rotational180, Symmetry::Rotational90]);
+        assert_eq!(detect_symmetries(&Grid::new()), Symmetry::ALL.to_vec());
+    }
+
+    #[test]
+    fn packed_bytes_round_trip() {
+        let grid = Grid::from_string("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79");
+        let bytes = grid.to_bytes();
+        assert_eq!(&bytes[..2], &[0x53, 0x00]);
+        assert_eq!(bytes[40], 0x90);
+        let back = Grid::from_bytes(&bytes).unwrap();
+        assert_eq!(back.values, grid.values);
+        assert_eq!(back.givens, grid.givens);
+
+        let mut bad = bytes;
+        bad[1] = 0x0A;
+        assert_eq!(Grid::from_bytes(&bad).err(), Some(ParseError::InvalidValue { index: 3, value: 10 }));
+    }
+
+    #[test]
+    fn from_values_round_trips() {
+        let puzzle = "49.7.....8..1..6.....82...192..6........4.7.2.....1..6..6.9.1..1......54..9.....8";
+        let mut expected = Grid::from_string(puzzle);
+        crate::solver::update_candidates(&mut expected);
+        let grid = Grid::from_values(&expected.values_array()).unwrap();
+        assert_eq!(grid.to_string(), puzzle);
+        assert_eq!(grid.candidates, expected.candidates);
+        assert_eq!(grid.givens, expected.givens);
+
+        let mut bad = [0u8; SIZE];
+        bad[5] = 10;
+        assert_eq!(Grid::from_values(&bad).err(), Some(ParseError::InvalidValue { index: 5, value: 10 }));
+    }
+
+    #[test]
+    fn wrong_full_grid_is_not_solved_correctly() {
+        let puzzle = Grid::from_string("49.7.....8..1..6.....82...192..6........4.7.2.....1..6..6.9.1..1......54..9.....8");
+        let mut solution = crate::solver::solve(&puzzle).unwrap();
+        assert!(solution.is_solved_correctly());
+        solution.values.swap(0, 1);
+        assert!(solution.is_solved() && !solution.is_solved_correctly());
+    }
+
+    #[test]
+    fn load_values_matches_fresh_parse() {
+        let puzzle = "49.7.....8..1..6.....82...192..6........4.7.2.....1..6..6.9.1..1......54..9.....8";
+        let mut expected = Grid::from_string(puzzle);
+        crate::solver::update_candidates(&mut expected);
+
+        let mut reused = Grid::from_string("123456789");
+        reused.load_values(puzzle);
+        assert_eq!(reused.values, expected.values);
+        assert_eq!(reused.candidates, expected.candidates);
+        assert_eq!(reused.givens, expected.givens);
+    }
+
+    #[test]
+    fn place_refuses_givens() {
+        let mut grid = Grid::from_string("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79");
+        update_candidates(&mut grid);
+        assert!(grid.is_given(0));
+        assert_eq!(grid.place(0, 5), Err(PlaceError::Given));
+        assert_eq!(grid.place(2, 4), Ok(()));
+        assert!(!grid.is_given(2));
+        assert_eq!(grid.place(2, 4), Err(PlaceError::CellFilled));
+    }
+
+    #[test]
+    fn place_reports_the_peers_it_changed() {
+        let mut grid = Grid::from_string("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79");
+        update_candidates(&mut grid);
+        let before = grid;
+        let changed = grid.place_reporting(2, 4).unwrap();
+        assert!(!changed.is_empty() && changed.iter().all(|&(_, bits)| bits == 1 << 3));
+        let expected: Vec<usize> = (0..SIZE).filter(|&c| c != 2 && before.candidates[c] != grid.candidates[c]).collect();
+        assert_eq!(changed.iter().map(|&(c, _)| c).collect::<Vec<_>>(), expected);
+        assert_eq!(grid.place_reporting(0, 5), Err(PlaceError::Given));
+    }
+
+    #[test]
+    fn extended_format_keeps_pencil_marks() {
+        let plain = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+        let mut expected = Grid::from_string(plain);
+        update_candidates(&mut expected);
+        expected.candidates[2] = Candidates::from_digit(1) | Candidates::from_digit(4);
+        let extended = format!("53{{14}} {}", &plain[3..]);
+        let grid = Grid::from_extended(&extended).unwrap();
+        assert_eq!(grid.candidates_string(), expected.candidates_string());
+        assert!(grid.is_given(0) && !grid.is_given(2));
+
+        assert_eq!(Grid::from_extended("53{14").err(), Some(ParseError::UnclosedBrace { index: 2 }));
+        assert_eq!(Grid::from_extended("5{}").err(), Some(ParseError::InvalidChar { index: 2, ch: '}' }));
+        assert_eq!(Grid::from_extended("5{1{").err(), Some(ParseError::InvalidChar { index: 3, ch: '{' }));
+        assert_eq!(Grid::from_extended("5x").err(), Some(ParseError::InvalidChar { index: 1, ch: 'x' }));
+        assert_eq!(Grid::from_extended("{12}5").err(), Some(ParseError::WrongLength(2)));
+    }
+
+    #[test]
+    fn transforms_preserve_canonical_form() {
+        let grid = Grid::from_string("4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......");
+        let variant = grid
+            .transpose()
+            .swap_bands(0, 2)
+            .swap_stacks(1, 2)
+            .swap_rows_within_band(1, 0, 2)
+            .swap_cols_within_stack(0, 0, 1)
+            .relabel([2, 3, 4, 5, 6, 7, 8, 9, 1]);
+        assert_eq!(variant.canonical_string(), grid.canonical_string());
+
+        let canonical = grid.canonical();
+        let reparsed = Grid::from_values(&canonical.values).unwrap();
+        assert_eq!((canonical.givens, canonical.candidates), (reparsed.givens, reparsed.candidates));
+    }
 }
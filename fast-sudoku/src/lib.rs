@@ -1,28 +1,571 @@
 
-mod utils;
-mod grid;
-mod solver;
-mod generator;
-mod difficulty;
-mod techniques;
+pub mod utils;
+pub mod grid;
+pub mod solver;
+pub mod generator;
+pub mod difficulty;
+pub mod techniques;
+pub mod rect;
 
 use wasm_bindgen::prelude::*;
 use generator::Generator;
+use grid::Grid;
+use techniques::{get_hint, Hint};
 
+/// Unseeded generation. Falls back to a per-call counter if the host has no
+/// usable entropy, so back-to-back calls never return the same puzzle.
+/// Throws if no puzzle rated inside the category band turned up.
 #[wasm_bindgen]
-pub fn generate_by_category_fast(category: &str) -> String {
+pub fn generate_by_category_fast(category: &str) -> Result<String, JsValue> {
     let mut gen = Generator::new();
-    gen.generate(category)
+    gen.generate(category).ok_or_else(|| js_error("no puzzle found in the category band"))
 }
 
+/// Seeded `generate_by_category_fast`; also throws on failure.
 #[wasm_bindgen]
-pub fn generate_with_seed_fast(category: &str, seed: u64) -> String {
+pub fn generate_with_seed_fast(category: &str, seed: u64) -> Result<String, JsValue> {
     let mut gen = Generator::new_with_seed(seed);
-    gen.generate(category)
+    gen.generate(category).ok_or_else(|| js_error("no puzzle found in the category band"))
 }
 
+/// Long-lived generator for JS callers producing many puzzles: seeded once,
+/// then reused across calls. Call `free()` when done.
+#[wasm_bindgen(js_name = Generator)]
+pub struct WasmGenerator {
+    inner: Generator,
+}
+
+#[wasm_bindgen(js_class = Generator)]
+impl WasmGenerator {
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u64) -> WasmGenerator {
+        WasmGenerator { inner: Generator::new_with_seed(seed) }
+    }
+
+    /// Puzzle rated inside the category band. Throws if none turned up.
+    pub fn generate(&mut self, category: &str) -> Result<String, JsValue> {
+        self.inner.generate(category).ok_or_else(|| js_error("no puzzle found in the category band"))
+    }
+
+    /// A random completed grid. Throws if filling failed.
+    pub fn generate_solution(&mut self) -> Result<String, JsValue> {
+        self.inner.generate_solution().map(|g| g.to_string()).ok_or_else(|| js_error("failed to fill a solution grid"))
+    }
+}
+
+/// Puzzle on a `box_rows * box_cols` grid with rectangular boxes (e.g. 2x3 for
+/// 6x6), solvable with singles and pairs. Throws for unsupported shapes.
+#[wasm_bindgen]
+pub fn generate_rect_fast(box_rows: usize, box_cols: usize, seed: u64) -> Result<String, JsValue> {
+    let side = box_rows * box_cols;
+    if side == 0 || side > rect::MAX_SIDE {
+        return Err(js_error("unsupported box shape"));
+    }
+    Ok(rect::generate(box_rows, box_cols, seed).to_string())
+}
+
+/// `template_str` is 81 chars of `1` (given) / `0` (blank). Throws if no
+/// unique in-band puzzle with that pattern was found.
+#[wasm_bindgen]
+pub fn generate_from_template_fast(template_str: &str, category: &str, seed: u64) -> Result<String, JsValue> {
+    let mut template = [false; grid::SIZE];
+    for (i, c) in template_str.chars().take(grid::SIZE).enumerate() {
+        template[i] = c == '1';
+    }
+    let mut gen = Generator::new_with_seed(seed);
+    gen.generate_from_template(&template, category).ok_or_else(|| js_error("no puzzle found for template"))
+}
+
+/// Deterministic puzzle for a date key (e.g. `20260101`). The same
+/// (category, date_seed) always yields the same puzzle for a given generator
+/// and rating model; the tests pin it so any change is deliberate. Throws
+/// when the category band can't be reached from that seed.
+#[wasm_bindgen]
+pub fn daily_puzzle_fast(category: &str, date_seed: u64) -> Result<String, JsValue> {
+    Generator::new_with_seed(date_seed).generate(category).ok_or_else(|| js_error("no daily puzzle for this category"))
+}
+
+/// Solution to `daily_puzzle_fast(category, date_seed)`, for offline validation.
+#[wasm_bindgen]
+pub fn daily_solution_fast(category: &str, date_seed: u64) -> Result<String, JsValue> {
+    let puzzle = Grid::from_string(&daily_puzzle_fast(category, date_seed)?);
+    solver::solve(&puzzle).map(|g| g.to_string()).ok_or_else(|| js_error("daily puzzle has no solution"))
+}
+
+/// Score from 1 to 100, or 0 when givens repeat in a unit (see `is_valid_fast`).
 #[wasm_bindgen]
 pub fn evaluate_difficulty_fast(puzzle_str: &str) -> i32 {
-    let grid = crate::grid::Grid::from_string(puzzle_str);
+    let grid = Grid::from_string(puzzle_str);
     crate::difficulty::evaluate_difficulty(&grid).score
 }
+
+/// Difficulty of what is left from a 729-char candidate string ("how hard is
+/// the rest from here?"), keeping the player's own eliminations. 0 if the
+/// values repeat in a unit; throws on an invalid string.
+#[wasm_bindgen]
+pub fn evaluate_remaining_difficulty_fast(candidate_str: &str) -> Result<i32, JsValue> {
+    let grid = Grid::from_candidates_string(candidate_str).map_err(|e| js_error(&format!("invalid candidate string: {:?}", e)))?;
+    Ok(difficulty::evaluate_difficulty_from_candidates(&grid).score)
+}
+
+/// Score plus the guesses a brute-force solver needs (0 when logic alone
+/// solves it) as JSON `{score, guess_depth}`. Much slower than
+/// `evaluate_difficulty_fast` on grids logic can't finish. Throws when givens
+/// repeat in a unit.
+#[wasm_bindgen]
+pub fn evaluate_with_guess_depth_fast(puzzle_str: &str) -> Result<String, JsValue> {
+    let detailed = difficulty::evaluate_with_guess_depth(&Grid::from_string(puzzle_str));
+    let guess_depth = detailed.guess_depth.ok_or_else(|| js_error("givens repeat in a unit"))?;
+    Ok(format!("{{\"score\":{},\"guess_depth\":{}}}", detailed.score, guess_depth))
+}
+
+#[wasm_bindgen]
+pub fn canonicalize_fast(puzzle_str: &str) -> String {
+    let grid = Grid::from_string(puzzle_str);
+    grid.canonical_string()
+}
+
+/// Category name, or `invalid` when givens repeat in a unit.
+#[wasm_bindgen]
+pub fn classify_fast(puzzle_str: &str) -> String {
+    let grid = Grid::from_string(puzzle_str);
+    difficulty::evaluate_difficulty(&grid).category().to_string()
+}
+
+/// Cheap gate before the solve/analyze calls: false if a digit repeats in a
+/// row, column or box. Says nothing about solvability or uniqueness.
+#[wasm_bindgen]
+pub fn is_valid_fast(puzzle_str: &str) -> bool {
+    Grid::from_string(puzzle_str).is_valid()
+}
+
+/// Checks a player's grid against the puzzle's unique solution. Returns JSON
+/// `{correct, wrong_cells}`; blanks make it incorrect but aren't listed as wrong.
+/// Throws if the puzzle has no unique solution.
+#[wasm_bindgen]
+pub fn check_solution_fast(puzzle_str: &str, candidate_solution_str: &str) -> Result<String, JsValue> {
+    let puzzle = Grid::from_string(puzzle_str);
+    let solution = match solver::solve_unique(&puzzle) {
+        Some((s, true)) => s,
+        _ => return Err(js_error("puzzle has no unique solution")),
+    };
+
+    let attempt = Grid::from_string(candidate_solution_str);
+    let diff = solution.diff(&attempt);
+    let wrong: Vec<String> = diff.iter().filter(|&&(_, _, new)| new != 0).map(|(cell, _, _)| cell.to_string()).collect();
+    Ok(format!("{{\"correct\":{},\"wrong_cells\":[{}]}}", diff.is_empty(), wrong.join(",")))
+}
+
+/// Solves and checks uniqueness in one search. Returns JSON
+/// `{solution, unique}`; throws if the puzzle has no solution.
+#[wasm_bindgen]
+pub fn solve_unique_fast(puzzle_str: &str) -> Result<String, JsValue> {
+    let puzzle = Grid::from_string(puzzle_str);
+    match solver::solve_unique(&puzzle) {
+        Some((solution, unique)) => Ok(format!("{{\"solution\":\"{}\",\"unique\":{}}}", solution, unique)),
+        None => Err(js_error("puzzle has no solution")),
+    }
+}
+
+/// One complete record per puzzle for catalogs: JSON `{puzzle, solution,
+/// unique, clue_count, score, category, techniques, symmetries}`, where
+/// `techniques` maps each technique used to its count in order of first use.
+/// Throws if the puzzle has no solution.
+#[wasm_bindgen]
+pub fn export_fast(puzzle_str: &str) -> Result<String, JsValue> {
+    let puzzle = Grid::from_string(puzzle_str);
+    let (solution, unique) = solver::solve_unique(&puzzle).ok_or_else(|| js_error("puzzle has no solution"))?;
+    let rating = difficulty::evaluate_difficulty_detailed(&puzzle);
+    let clue_count = puzzle.clue_count();
+
+    let techniques: Vec<String> = rating.techniques.iter().map(|(name, count)| format!("\"{}\":{}", name, count)).collect();
+    let symmetries: Vec<String> = grid::detect_symmetries(&puzzle).iter().map(|s| format!("\"{}\"", s.name())).collect();
+    Ok(format!(
+        "{{\"puzzle\":\"{}\",\"solution\":\"{}\",\"unique\":{},\"clue_count\":{},\"score\":{},\"category\":\"{}\",\"techniques\":{{{}}},\"symmetries\":[{}]}}",
+        puzzle,
+        solution,
+        unique,
+        clue_count,
+        rating.score,
+        rating.category(),
+        techniques.join(","),
+        symmetries.join(",")
+    ))
+}
+
+/// Correct digit for `cell` ("just tell me"). Throws if the cell is out of
+/// range or already filled, or the puzzle is unsolvable.
+#[wasm_bindgen]
+pub fn reveal_cell_fast(puzzle_str: &str, cell: usize) -> Result<u8, JsValue> {
+    if cell >= grid::SIZE {
+        return Err(js_error("cell out of range"));
+    }
+    Grid::from_string(puzzle_str).solved_value(cell).ok_or_else(|| js_error("cell is filled or puzzle is unsolvable"))
+}
+
+/// "What goes here and why?" for one cell: JSON `{logical, cell, digit}`,
+/// plus `notation` and the hint fields when a single places it now. Otherwise
+/// `logical` is false and the digit comes from the solution. Throws like
+/// `reveal_cell_fast`.
+#[wasm_bindgen]
+pub fn hint_for_cell_fast(puzzle_str: &str, cell: usize) -> Result<String, JsValue> {
+    let digit = reveal_cell_fast(puzzle_str, cell)?;
+    let mut grid = Grid::from_string(puzzle_str);
+    solver::update_candidates(&mut grid);
+    Ok(match techniques::single_for_cell(&grid, cell) {
+        Some(hint) => format!(
+            "{{\"logical\":true,\"cell\":{},\"digit\":{},\"notation\":\"{}\",{}}}",
+            cell,
+            digit,
+            hint.to_notation(),
+            hint_json_fields(&hint)
+        ),
+        None => format!("{{\"logical\":false,\"cell\":{},\"digit\":{}}}", cell, digit),
+    })
+}
+
+/// 81-entry JSON array: the difficulty of the technique that placed each cell
+/// on the logical solve path, `0` for givens and `null` if logic never got there.
+#[wasm_bindgen]
+pub fn difficulty_heatmap_fast(puzzle_str: &str) -> String {
+    let grid = Grid::from_string(puzzle_str);
+    let mut heat: Vec<Option<f32>> = grid.values.iter().map(|&v| if v != 0 { Some(0.0) } else { None }).collect();
+
+    let (_, steps) = solver::solve_logically(&grid);
+    for hint in &steps {
+        for &(cell, _) in &hint.placements {
+            if heat[cell].is_none() {
+                heat[cell] = Some(hint.difficulty);
+            }
+        }
+    }
+
+    let items: Vec<String> = heat.iter().map(|h| h.map_or("null".to_string(), |d| d.to_string())).collect();
+    format!("[{}]", items.join(","))
+}
+
+/// The whole logical solve as a replayable script: a JSON array with one
+/// `{technique, subtype, difficulty, impact, placements, eliminations, cells,
+/// grid, candidates}` object per step, where `cells` are the pattern cells to
+/// highlight and `grid`/`candidates` the state after the step. Ends early,
+/// unsolved, where `get_hint` gets stuck.
+#[wasm_bindgen]
+pub fn solve_script_fast(puzzle_str: &str) -> String {
+    let mut grid = Grid::from_string(puzzle_str);
+    solver::update_candidates(&mut grid);
+    let mut steps = Vec::new();
+
+    while !grid.is_solved() {
+        let Some(hint) = get_hint(&grid) else { break };
+        difficulty::apply_hint(&mut grid, &hint);
+        let cells: Vec<String> = hint.base_cells.iter().map(|c| c.to_string()).collect();
+        steps.push(format!(
+            "{{{},\"cells\":[{}],\"grid\":\"{}\",\"candidates\":\"{}\"}}",
+            hint_json_fields(&hint),
+            cells.join(","),
+            grid,
+            grid.candidates_string()
+        ));
+    }
+    format!("[{}]", steps.join(","))
+}
+
+/// Initial pencil marks for a puzzle, as a 729-char candidate string.
+#[wasm_bindgen]
+pub fn compute_candidates_fast(puzzle_str: &str) -> String {
+    let mut grid = Grid::from_string(puzzle_str);
+    solver::update_candidates(&mut grid);
+    grid.candidates_string()
+}
+
+/// Fills every cell reachable by naked and hidden singles alone and returns
+/// the resulting puzzle string: no eliminations, no guessing. The editor's
+/// "fill the easy bits" button; trivial puzzles come back solved.
+#[wasm_bindgen]
+pub fn fill_singles_fast(puzzle_str: &str) -> String {
+    let mut grid = Grid::from_string(puzzle_str);
+    solver::update_candidates(&mut grid);
+    solver::propagate_singles(&mut grid);
+    grid.to_string()
+}
+
+/// Initial pencil marks as 81 raw candidate masks (bit `d - 1` for digit `d`,
+/// filled cells holding their value's bit), a `Uint16Array` in JS. Cheaper than
+/// the candidate string for clients that keep their own state.
+#[wasm_bindgen]
+pub fn get_candidates_u16_fast(puzzle_str: &str) -> Vec<u16> {
+    let mut grid = Grid::from_string(puzzle_str);
+    solver::update_candidates(&mut grid);
+    grid.candidate_masks().to_vec()
+}
+
+/// Inverse of `get_candidates_u16_fast`: the 729-char candidate string for
+/// 81 candidate masks, a lone candidate no peer shares counting as filled.
+/// Throws on a wrong length or bits above digit 9.
+#[wasm_bindgen]
+pub fn puzzle_from_candidates_u16_fast(masks: &[u16]) -> Result<String, JsValue> {
+    let grid = Grid::from_candidate_masks(masks).map_err(|e| js_error(&format!("invalid candidate masks: {:?}", e)))?;
+    Ok(grid.candidates_string())
+}
+
+/// The next hint with the candidate strings just before and after applying
+/// it, for animating pencil marks: JSON `{stuck, before, after}` plus the hint
+/// fields. When stuck (or solved) there is no hint and `after` equals `before`;
+/// a broken board also carries `contradiction`, the cell left without candidates.
+#[wasm_bindgen]
+pub fn preview_hint_fast(puzzle_str: &str) -> String {
+    let mut grid = Grid::from_string(puzzle_str);
+    solver::update_candidates(&mut grid);
+    let before = grid.candidates_string();
+
+    if let Some(cell) = techniques::find_contradiction(&grid) {
+        return format!("{{\"stuck\":true,\"contradiction\":{},\"before\":\"{}\",\"after\":\"{}\"}}", cell, before, before);
+    }
+    match get_hint(&grid) {
+        Some(hint) => {
+            difficulty::apply_hint(&mut grid, &hint);
+            format!(
+                "{{\"stuck\":false,\"before\":\"{}\",\"after\":\"{}\",{}}}",
+                before,
+                grid.candidates_string(),
+                hint_json_fields(&hint)
+            )
+        }
+        None => format!("{{\"stuck\":true,\"before\":\"{}\",\"after\":\"{}\"}}", before, before),
+    }
+}
+
+/// Applies one named technique to a 729-char candidate string (see
+/// `compute_candidates_fast`) and returns the updated candidate string. The
+/// input comes back unchanged if the technique finds nothing; an invalid input
+/// or technique name throws.
+#[wasm_bindgen]
+pub fn apply_technique_fast(candidate_str: &str, technique_name: &str) -> Result<String, JsValue> {
+    let mut grid = match Grid::from_candidates_string(candidate_str) {
+        Ok(g) => g,
+        Err(e) => return Err(js_error(&format!("invalid candidate string: {:?}", e))),
+    };
+    if techniques::technique_difficulty(technique_name) == 0.0 {
+        return Err(js_error(&format!("unknown technique: {}", technique_name)));
+    }
+    if let Some(hint) = techniques::detect_technique(&grid, technique_name) {
+        difficulty::apply_hint(&mut grid, &hint);
+    }
+    Ok(grid.candidates_string())
+}
+
+/// Next hint for a board given as a 729-char candidate string, keeping the
+/// player's own eliminations. Returns JSON `{stuck}` plus the hint fields when
+/// one is found, or `{stuck, contradiction}` naming a cell the player left
+/// without candidates; throws on an invalid string.
+#[wasm_bindgen]
+pub fn hint_from_candidates_fast(candidate_str: &str) -> Result<String, JsValue> {
+    let grid = Grid::from_candidates_string(candidate_str).map_err(|e| js_error(&format!("invalid candidate string: {:?}", e)))?;
+    if let Some(cell) = techniques::find_contradiction(&grid) {
+        return Ok(format!("{{\"stuck\":true,\"contradiction\":{}}}", cell));
+    }
+    Ok(match techniques::get_hint_from_candidates(&grid.values, &grid.candidates) {
+        Some(hint) => format!("{{\"stuck\":false,{}}}", hint_json_fields(&hint)),
+        None => "{\"stuck\":true}".to_string(),
+    })
+}
+
+/// Rates newline-separated puzzles in one call. Each output line is
+/// `score,solvable,hardest_technique,clue_count`, with `invalid` in place of
+/// the score when givens repeat in a unit; blank input lines are skipped.
+#[wasm_bindgen]
+pub fn rate_batch_fast(puzzles: &str) -> String {
+    let mut lines = Vec::new();
+    for line in puzzles.lines() {
+        let line = line.trim();
+        if line.is_empty() { continue; }
+        let grid = Grid::from_string(line);
+        let result = difficulty::evaluate_difficulty_detailed(&grid);
+        let clue_count = grid.clue_count();
+        let score = if result.valid { result.score.to_string() } else { difficulty::INVALID_CATEGORY.to_string() };
+        lines.push(format!("{},{},{},{}", score, result.solvable, result.hardest_technique, clue_count));
+    }
+    lines.join("\n")
+}
+
+/// Uniqueness check for newline-separated puzzles: one `true`/`false` line per
+/// puzzle, blank input lines skipped.
+#[wasm_bindgen]
+pub fn check_unique_batch_fast(puzzles: &str) -> String {
+    let puzzles: Vec<String> = puzzles.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect();
+    let results: Vec<String> = solver::check_unique_batch(&puzzles).iter().map(|u| u.to_string()).collect();
+    results.join("\n")
+}
+
+/// Applies the next logical move and returns JSON `{done, stuck, grid,
+/// candidates, technique, subtype, difficulty, impact, placements,
+/// eliminations}`. Takes a puzzle string or a 729-char candidate string (see
+/// `compute_candidates_fast`); feed `candidates` back in to keep moves that
+/// only eliminate, since `grid` holds values alone. A broken board reports
+/// `stuck` with `contradiction`, the cell left without candidates. Throws on
+/// a malformed candidate string.
+#[wasm_bindgen]
+pub fn solve_step_fast(state_str: &str) -> Result<String, JsValue> {
+    let mut grid = if state_str.chars().count() == grid::SIZE * 9 {
+        Grid::from_candidates_string(state_str).map_err(|e| js_error(&format!("invalid candidate string: {:?}", e)))?
+    } else {
+        let mut grid = Grid::from_string(state_str);
+        solver::update_candidates(&mut grid);
+        grid
+    };
+
+    if grid.is_solved() {
+        return Ok(format!("{{\"done\":true,\"stuck\":false,\"grid\":\"{}\",\"candidates\":\"{}\"}}", grid, grid.candidates_string()));
+    }
+    if let Some(cell) = techniques::find_contradiction(&grid) {
+        return Ok(format!(
+            "{{\"done\":false,\"stuck\":true,\"contradiction\":{},\"grid\":\"{}\",\"candidates\":\"{}\"}}",
+            cell,
+            grid,
+            grid.candidates_string()
+        ));
+    }
+
+    Ok(match get_hint(&grid) {
+        Some(hint) => {
+            difficulty::apply_hint(&mut grid, &hint);
+            format!(
+                "{{\"done\":{},\"stuck\":false,\"grid\":\"{}\",\"candidates\":\"{}\",{}}}",
+                grid.is_solved(),
+                grid,
+                grid.candidates_string(),
+                hint_json_fields(&hint)
+            )
+        }
+        None => format!("{{\"done\":false,\"stuck\":true,\"grid\":\"{}\",\"candidates\":\"{}\"}}", grid, grid.candidates_string()),
+    })
+}
+
+/// Failures from exported functions surface in JS as a thrown `Error`-like value
+/// carrying `msg`, never as an empty-string sentinel.
+fn js_error(msg: &str) -> JsValue {
+    JsValue::from_str(msg)
+}
+
+fn hint_json_fields(hint: &Hint) -> String {
+    format!(
+        "\"technique\":\"{}\",\"subtype\":{},\"difficulty\":{},\"impact\":{},\"placements\":{},\"eliminations\":{}",
+        hint.technique,
+        hint.subtype,
+        hint.difficulty,
+        hint.impact(),
+        pairs_json(&hint.placements),
+        pairs_json(&hint.eliminations)
+    )
+}
+
+fn pairs_json(pairs: &[(usize, u8)]) -> String {
+    let items: Vec<String> = pairs.iter().map(|(cell, digit)| format!("[{},{}]", cell, digit)).collect();
+    format!("[{}]", items.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daily_puzzle_is_stable() {
+        let puzzle = daily_puzzle_fast("trivial", 20260101).unwrap();
+        assert_eq!(puzzle, daily_puzzle_fast("trivial", 20260101).unwrap());
+        assert_eq!(puzzle, "...3....8129...6........59....93..4.7..1.......16.7.5..96..1..38...........56.1..");
+
+        let solution = daily_solution_fast("trivial", 20260101).unwrap();
+        assert!(puzzle.chars().zip(solution.chars()).all(|(p, s)| p == '.' || p == s));
+        assert!(!solution.contains('.'));
+    }
+
+    #[test]
+    fn export_describes_puzzle() {
+        let puzzle = daily_puzzle_fast("trivial", 20260101).unwrap();
+        let record = export_fast(&puzzle).unwrap();
+        assert!(record.starts_with(&format!("{{\"puzzle\":\"{}\",\"solution\":\"{}\",\"unique\":true,", puzzle, daily_solution_fast("trivial", 20260101).unwrap())));
+        assert!(record.contains("\"category\":\"trivial\"") && record.contains("\"naked_single\":"));
+    }
+
+    #[test]
+    fn validity_gate_catches_repeated_givens() {
+        assert!(is_valid_fast("53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79"));
+        assert!(is_valid_fast(""));
+        assert!(!is_valid_fast("5...5"));
+        assert!(!is_valid_fast("5........5"));
+    }
+
+    #[test]
+    fn guess_depth_comes_with_the_score() {
+        let puzzle = daily_puzzle_fast("trivial", 20260101).unwrap();
+        let expected = format!("{{\"score\":{},\"guess_depth\":0}}", evaluate_difficulty_fast(&puzzle));
+        assert_eq!(evaluate_with_guess_depth_fast(&puzzle).unwrap(), expected);
+    }
+
+    #[test]
+    fn candidate_masks_come_back_as_a_candidate_string() {
+        let masks = get_candidates_u16_fast("4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......");
+        let candidates = puzzle_from_candidates_u16_fast(&masks).unwrap();
+        assert_eq!(candidates.len(), 729);
+        assert_eq!(Grid::from_candidates_string(&candidates).unwrap().candidate_masks().to_vec(), masks);
+    }
+
+    #[test]
+    fn solve_step_keeps_eliminations_through_candidates() {
+        let puzzle = "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+        let mut state = puzzle.to_string();
+        let mut eliminations_only = 0;
+        for _ in 0..200 {
+            let json = solve_step_fast(&state).unwrap();
+            if json.starts_with("{\"done\":true") { break; }
+            assert!(json.contains("\"stuck\":false"), "{}", json);
+            if json.contains("\"placements\":[]") { eliminations_only += 1; }
+            let start = json.find("\"candidates\":\"").unwrap() + 14;
+            state = json[start..start + 729].to_string();
+        }
+        assert!(eliminations_only > 0);
+        assert_eq!(Grid::from_candidates_string(&state).unwrap().to_string(), solver::solve(&Grid::from_string(puzzle)).unwrap().to_string());
+    }
+
+    #[test]
+    fn invalid_grids_are_labelled_not_rated() {
+        assert_eq!(classify_fast("11"), "invalid");
+        assert_eq!(evaluate_difficulty_fast("11"), 0);
+        assert_eq!(rate_batch_fast("11\n"), "invalid,false,,2");
+    }
+
+    #[test]
+    fn fill_singles_stops_where_singles_run_out() {
+        let easy = "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+        assert_eq!(fill_singles_fast(easy), solver::solve(&Grid::from_string(easy)).unwrap().to_string());
+
+        let hard = "8..........36......7..9.2...5...7.......457.....1...3...1....68..85...1..9....4..";
+        assert_eq!(fill_singles_fast(hard), Grid::from_string(hard).to_string());
+    }
+
+    #[test]
+    fn solve_script_replays_to_the_solution() {
+        let puzzle = "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+        let script = solve_script_fast(puzzle);
+        let solution = solver::solve(&Grid::from_string(puzzle)).unwrap().to_string();
+        assert!(script.starts_with("[{\"technique\":") && script.ends_with(&format!("\"grid\":\"{}\",\"candidates\":\"{}\"}}]", solution, Grid::from_string(&solution).candidates_string())));
+        assert_eq!(script.matches("\"technique\":").count(), solver::solve_logically(&Grid::from_string(puzzle)).1.len());
+        assert!(script.contains("\"technique\":\"pointing_pairs\""));
+    }
+
+    #[test]
+    fn cell_hint_prefers_singles_then_solution() {
+        let puzzle = "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......";
+        let solution = solver::solve(&Grid::from_string(puzzle)).unwrap();
+        let mut logical = 0;
+        for cell in (0..grid::SIZE).filter(|&c| puzzle.as_bytes()[c] == b'.') {
+            let json = hint_for_cell_fast(puzzle, cell).unwrap();
+            assert!(json.contains(&format!("\"digit\":{}", solution.values[cell])), "{}", json);
+            if json.starts_with("{\"logical\":true") {
+                assert!(json.contains(&format!("[[{},{}]]", cell, solution.values[cell])), "{}", json);
+                logical += 1;
+            }
+        }
+        assert!(logical > 0 && logical < 64);
+    }
+}
@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock};
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use crate::grid::ParseError;
+use crate::techniques::{technique_difficulty, Hint};
+
+/// Largest supported side length. Digits above 9 are written `A`, `B`, ...
+pub const MAX_SIDE: usize = 16;
+
+type LayoutCache = HashMap<(usize, usize), Arc<Layout>>; // By (box_rows, box_cols)
+
+/// Unit and peer tables for one box shape, shared by every grid of that shape.
+#[derive(Debug)]
+struct Layout {
+    side: usize,
+    units: Vec<Vec<usize>>, // Rows, then columns, then boxes
+    peers: Vec<Vec<usize>>,
+}
+
+impl Layout {
+    fn new(box_rows: usize, box_cols: usize) -> Self {
+        let side = box_rows * box_cols;
+        let mut units: Vec<Vec<usize>> = Vec::with_capacity(side * 3);
+        for r in 0..side {
+            units.push((0..side).map(|c| r * side + c).collect());
+        }
+        for c in 0..side {
+            units.push((0..side).map(|r| r * side + c).collect());
+        }
+        // Boxes are box_rows tall and box_cols wide, read left to right, top to bottom
+        let boxes_per_band = side / box_cols;
+        for b in 0..side {
+            let top = (b / boxes_per_band) * box_rows;
+            let left = (b % boxes_per_band) * box_cols;
+            let mut cells = Vec::with_capacity(side);
+            for r in top..top + box_rows {
+                for c in left..left + box_cols {
+                    cells.push(r * side + c);
+                }
+            }
+            units.push(cells);
+        }
+
+        let mut peers: Vec<Vec<usize>> = vec![Vec::new(); side * side];
+        for unit in &units {
+            for &cell in unit {
+                for &other in unit {
+                    if other != cell && !peers[cell].contains(&other) {
+                        peers[cell].push(other);
+                    }
+                }
+            }
+        }
+        Layout { side, units, peers }
+    }
+
+    /// The cached layout for the shape, built on first use.
+    fn shared(box_rows: usize, box_cols: usize) -> Arc<Self> {
+        static LAYOUTS: OnceLock<Mutex<LayoutCache>> = OnceLock::new();
+        let mut layouts = LAYOUTS.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(layouts.entry((box_rows, box_cols)).or_insert_with(|| Arc::new(Layout::new(box_rows, box_cols))))
+    }
+}
+
+/// Sudoku with `box_rows` x `box_cols` boxes on a side of `box_rows * box_cols`
+/// (6x6 with 2x3 boxes, 12x12 with 3x4, ...). Slower than the fixed 9x9 `Grid`
+/// but covers the solver, singles/pairs techniques and generation.
+#[derive(Clone, Debug)]
+pub struct GridRect {
+    box_rows: usize,
+    box_cols: usize,
+    layout: Arc<Layout>,
+    pub values: Vec<u8>,
+    pub candidates: Vec<u16>, // Bit d-1 set if d is possible; only meaningful where values[cell] == 0
+}
+
+impl GridRect {
+    /// Empty grid. Panics unless the side `box_rows * box_cols` is 1..=MAX_SIDE.
+    pub fn new(box_rows: usize, box_cols: usize) -> Self {
+        let side = box_rows * box_cols;
+        assert!(side > 0 && side <= MAX_SIDE, "unsupported box shape {}x{}", box_rows, box_cols);
+        GridRect {
+            box_rows,
+            box_cols,
+            layout: Layout::shared(box_rows, box_cols),
+            values: vec![0; side * side],
+            candidates: vec![full_mask(side); side * side],
+        }
+    }
+
+    /// Exactly side² chars: `.` or `0` for blanks, then `1`-`9`, `A`, `B`, ...
+    /// Candidates are computed from the givens.
+    pub fn from_string(box_rows: usize, box_cols: usize, s: &str) -> Result<Self, ParseError> {
+        let mut grid = GridRect::new(box_rows, box_cols);
+        let len = s.chars().count();
+        if len != grid.values.len() {
+            return Err(ParseError::WrongLength(len));
+        }
+        for (i, ch) in s.chars().enumerate() {
+            match digit_from_char(ch) {
+                Some(0) => {}
+                Some(d) if (d as usize) <= grid.side() => grid.values[i] = d,
+                _ => return Err(ParseError::InvalidChar { index: i, ch }),
+            }
+        }
+        grid.update_candidates();
+        Ok(grid)
+    }
+
+    pub fn side(&self) -> usize {
+        self.layout.side
+    }
+
+    pub fn box_dims(&self) -> (usize, usize) {
+        (self.box_rows, self.box_cols)
+    }
+
+    /// Every row, column and box, in that order.
+    pub fn units(&self) -> &[Vec<usize>] {
+        &self.layout.units
+    }
+
+    pub fn peers(&self, cell: usize) -> &[usize] {
+        &self.layout.peers[cell]
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.values.iter().all(|&v| v != 0)
+    }
+
+    /// Recomputes every candidate from the placed values. False if some empty
+    /// cell is left with none.
+    pub fn update_candidates(&mut self) -> bool {
+        let full = full_mask(self.side());
+        for cell in 0..self.values.len() {
+            if self.values[cell] != 0 {
+                self.candidates[cell] = 0;
+                continue;
+            }
+            let mut mask = full;
+            for &peer in &self.layout.peers[cell] {
+                if self.values[peer] != 0 {
+                    mask &= !(1 << (self.values[peer] - 1));
+                }
+            }
+            if mask == 0 {
+                return false;
+            }
+            self.candidates[cell] = mask;
+        }
+        true
+    }
+
+    /// Places `digit` and removes it from the peers. False if a peer runs out of
+    /// candidates.
+    pub fn assign(&mut self, cell: usize, digit: u8) -> bool {
+        self.values[cell] = digit;
+        self.candidates[cell] = 0;
+        let bit = 1u16 << (digit - 1);
+        let layout = Arc::clone(&self.layout);
+        for &peer in &layout.peers[cell] {
+            if self.values[peer] == 0 {
+                self.candidates[peer] &= !bit;
+                if self.candidates[peer] == 0 {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl fmt::Display for GridRect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s: String = self.values.iter().map(|&v| if v == 0 { '.' } else { char_from_digit(v) }).collect();
+        f.write_str(&s)
+    }
+}
+
+fn full_mask(side: usize) -> u16 {
+    ((1u32 << side) - 1) as u16
+}
+
+fn digit_from_char(ch: char) -> Option<u8> {
+    match ch {
+        '.' | '0' => Some(0),
+        '1'..='9' => Some(ch as u8 - b'0'),
+        'A'..='G' => Some(ch as u8 - b'A' + 10),
+        _ => None,
+    }
+}
+
+fn char_from_digit(d: u8) -> char {
+    if d <= 9 { (b'0' + d) as char } else { (b'A' + d - 10) as char }
+}
+
+pub fn solve(grid: &GridRect) -> Option<GridRect> {
+    let mut solution = grid.clone();
+    if !solution.update_candidates() {
+        return None;
+    }
+    if search(&mut solution, &mut None) { Some(solution) } else { None }
+}
+
+/// Like `solve`, with digits tried in random order; used to fill empty grids.
+pub fn solve_randomized(grid: &GridRect, rng: &mut SmallRng) -> Option<GridRect> {
+    let mut solution = grid.clone();
+    if !solution.update_candidates() {
+        return None;
+    }
+    if search(&mut solution, &mut Some(rng)) { Some(solution) } else { None }
+}
+
+pub fn is_unique(grid: &GridRect) -> bool {
+    let mut g = grid.clone();
+    if !g.update_candidates() {
+        return false;
+    }
+    let mut count = 0;
+    count_solutions(&g, &mut count);
+    count == 1
+}
+
+// Most-constrained empty cell, or None when the grid is full
+fn best_cell(grid: &GridRect) -> Option<usize> {
+    let mut best = None;
+    let mut min_candidates = u32::MAX;
+    for i in 0..grid.values.len() {
+        if grid.values[i] == 0 {
+            let c = grid.candidates[i].count_ones();
+            if c < min_candidates {
+                min_candidates = c;
+                best = Some(i);
+                if c <= 1 { break; }
+            }
+        }
+    }
+    best
+}
+
+fn digits_of(mask: u16) -> Vec<u8> {
+    (1..=16u8).filter(|&d| (mask >> (d - 1)) & 1 == 1).collect()
+}
+
+fn search(grid: &mut GridRect, rng: &mut Option<&mut SmallRng>) -> bool {
+    let cell = match best_cell(grid) {
+        Some(cell) => cell,
+        None => return true,
+    };
+    let mut digits = digits_of(grid.candidates[cell]);
+    if let Some(rng) = rng.as_deref_mut() {
+        digits.shuffle(rng);
+    }
+    for digit in digits {
+        let mut next = grid.clone();
+        if next.assign(cell, digit) && search(&mut next, rng) {
+            *grid = next;
+            return true;
+        }
+    }
+    false
+}
+
+fn count_solutions(grid: &GridRect, count: &mut usize) {
+    if *count > 1 { return; }
+    let cell = match best_cell(grid) {
+        Some(cell) => cell,
+        None => {
+            *count += 1;
+            return;
+        }
+    };
+    for digit in digits_of(grid.candidates[cell]) {
+        let mut next = grid.clone();
+        if next.assign(cell, digit) {
+            count_solutions(&next, count);
+        }
+    }
+}
+
+/// Next move using the techniques available at any size: naked and hidden
+/// singles, then naked and hidden pairs.
+pub fn get_hint(grid: &GridRect) -> Option<Hint> {
+    detect_naked_single(grid)
+        .or_else(|| detect_hidden_single(grid))
+        .or_else(|| detect_naked_pair(grid))
+        .or_else(|| detect_hidden_pair(grid))
+}
+
+fn hint(technique: &'static str, eliminations: Vec<(usize, u8)>, placements: Vec<(usize, u8)>) -> Option<Hint> {
+    Some(Hint {
+        difficulty: technique_difficulty(technique),
+        technique,
+        eliminations,
+        placements,
+        base_cells: vec![],
+        subtype: 0,
+        search_cost: 0,
+    })
+}
+
+fn detect_naked_single(grid: &GridRect) -> Option<Hint> {
+    for i in 0..grid.values.len() {
+        if grid.values[i] == 0 && grid.candidates[i].count_ones() == 1 {
+            let digit = grid.candidates[i].trailing_zeros() as u8 + 1;
+            return hint("naked_single", vec![], vec![(i, digit)]);
+        }
+    }
+    None
+}
+
+fn detect_hidden_single(grid: &GridRect) -> Option<Hint> {
+    for unit in grid.units() {
+        for d in 1..=grid.side() as u8 {
+            if unit.iter().any(|&cell| grid.values[cell] == d) { continue; }
+            let bit = 1u16 << (d - 1);
+            let cells: Vec<usize> = unit.iter().copied().filter(|&c| grid.values[c] == 0 && grid.candidates[c] & bit != 0).collect();
+            if cells.len() == 1 {
+                return hint("hidden_single", vec![], vec![(cells[0], d)]);
+            }
+        }
+    }
+    None
+}
+
+fn detect_naked_pair(grid: &GridRect) -> Option<Hint> {
+    for unit in grid.units() {
+        let pairs: Vec<usize> = unit.iter().copied().filter(|&c| grid.values[c] == 0 && grid.candidates[c].count_ones() == 2).collect();
+        for i in 0..pairs.len() {
+            for j in i + 1..pairs.len() {
+                let mask = grid.candidates[pairs[i]];
+                if grid.candidates[pairs[j]] != mask { continue; }
+
+                let mut eliminations = Vec::new();
+                for &cell in unit {
+                    if cell == pairs[i] || cell == pairs[j] || grid.values[cell] != 0 { continue; }
+                    for d in digits_of(grid.candidates[cell] & mask) {
+                        eliminations.push((cell, d));
+                    }
+                }
+                if !eliminations.is_empty() {
+                    return hint("naked_pairs", eliminations, vec![]);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn detect_hidden_pair(grid: &GridRect) -> Option<Hint> {
+    for unit in grid.units() {
+        let side = grid.side() as u8;
+        let cells_of = |d: u8| -> Vec<usize> {
+            unit.iter().copied().filter(|&c| grid.values[c] == 0 && (grid.candidates[c] >> (d - 1)) & 1 == 1).collect()
+        };
+        for d1 in 1..=side {
+            let cells = cells_of(d1);
+            if cells.len() != 2 { continue; }
+            for d2 in d1 + 1..=side {
+                if cells_of(d2) != cells { continue; }
+
+                let keep = (1u16 << (d1 - 1)) | (1u16 << (d2 - 1));
+                let mut eliminations = Vec::new();
+                for &cell in &cells {
+                    for d in digits_of(grid.candidates[cell] & !keep) {
+                        eliminations.push((cell, d));
+                    }
+                }
+                if !eliminations.is_empty() {
+                    return hint("hidden_pairs", eliminations, vec![]);
+                }
+            }
+        }
+    }
+    None
+}
+
+pub fn apply_hint(grid: &mut GridRect, hint: &Hint) {
+    for &(cell, digit) in &hint.placements {
+        grid.assign(cell, digit);
+    }
+    for &(cell, digit) in &hint.eliminations {
+        grid.candidates[cell] &= !(1 << (digit - 1));
+    }
+}
+
+/// True if `get_hint` alone solves the grid.
+pub fn is_logically_solvable(grid: &GridRect) -> bool {
+    let mut g = grid.clone();
+    if !g.update_candidates() {
+        return false;
+    }
+    while !g.is_solved() {
+        match get_hint(&g) {
+            Some(hint) => apply_hint(&mut g, &hint),
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Minimal-ish puzzle for the box shape: a random solution with clues removed
+/// in random order as long as the answer stays unique and the singles/pairs
+/// techniques still solve it, which keeps small grids friendly for beginners.
+/// Each removal runs a full uniqueness check, so 12x12 takes around a second
+/// (up to several) in release builds and far longer in debug ones.
+pub fn generate(box_rows: usize, box_cols: usize, seed: u64) -> GridRect {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let solution = solve_randomized(&GridRect::new(box_rows, box_cols), &mut rng)
+        .expect("an empty grid always has a solution");
+
+    let mut puzzle = solution.clone();
+    let mut cells: Vec<usize> = (0..puzzle.values.len()).collect();
+    cells.shuffle(&mut rng);
+    for cell in cells {
+        let value = puzzle.values[cell];
+        puzzle.values[cell] = 0;
+        if !is_unique(&puzzle) || !is_logically_solvable(&puzzle) {
+            puzzle.values[cell] = value;
+        }
+    }
+    puzzle.update_candidates();
+    puzzle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_unique_six_by_six() {
+        let puzzle = generate(2, 3, 42);
+        assert_eq!(puzzle.to_string().len(), 36);
+        assert!(puzzle.values.contains(&0));
+        assert!(is_unique(&puzzle));
+
+        let solution = solve(&puzzle).unwrap();
+        for unit in solution.units() {
+            let mut digits: Vec<u8> = unit.iter().map(|&c| solution.values[c]).collect();
+            digits.sort();
+            assert_eq!(digits, (1..=6).collect::<Vec<u8>>());
+        }
+    }
+
+    #[test]
+    fn grids_of_one_shape_share_a_layout() {
+        let (a, b) = (GridRect::new(2, 3), GridRect::new(2, 3));
+        assert!(Arc::ptr_eq(&a.layout, &b.layout));
+        assert!(!Arc::ptr_eq(&a.layout, &GridRect::new(3, 2).layout));
+    }
+
+    #[test]
+    #[ignore = "seconds in release, minutes in debug; run with --release -- --ignored"]
+    fn generates_unique_twelve_by_twelve() {
+        let puzzle = generate(3, 4, 1);
+        assert!(puzzle.values.contains(&0));
+        assert!(is_unique(&puzzle));
+        assert!(is_logically_solvable(&puzzle));
+    }
+
+    #[test]
+    fn twelve_by_twelve_round_trips() {
+        let mut rng = SmallRng::seed_from_u64(7);
+        let solution = solve_randomized(&GridRect::new(3, 4), &mut rng).unwrap();
+        let parsed = GridRect::from_string(3, 4, &solution.to_string()).unwrap();
+        assert!(parsed.is_solved());
+        assert_eq!(parsed.values, solution.values);
+        assert!(GridRect::from_string(2, 3, "12").is_err());
+    }
+}
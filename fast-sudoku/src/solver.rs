@@ -1,52 +1,359 @@
 
-use crate::grid::{Grid, SIZE};
+use crate::grid::{Candidates, Grid, SIZE};
+use crate::utils::{ROWS, COLS, BOXES, box_of, col_of, row_of};
+use crate::techniques::{get_hint, Hint};
+use crate::difficulty::apply_hint;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
 
 pub fn solve(grid: &Grid) -> Option<Grid> {
+    if !grid.is_valid() { return None; } // Conflicting givens
     let mut solution = *grid;
     // We need to update candidates based on initial values first
     update_candidates(&mut solution);
     
-    if solve_recursive(&mut solution) {
+    if solve_recursive(&mut solution, &mut None) {
         Some(solution)
     } else {
         None
     }
 }
 
+/// One step of `solve_with_trace`, in the order the backtracking search took it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveEvent {
+    /// `digit` was the only candidate left for `cell`.
+    Single { cell: usize, digit: u8 },
+    /// `digit` was tried in `cell` as guess number `depth` on the current path (1 = outermost).
+    Guess { cell: usize, digit: u8, depth: usize },
+    /// The guess at `depth` led to a contradiction and everything since it was undone.
+    Backtrack { depth: usize },
+}
+
+/// `solve` that also records every placement and backtrack, so a caller can see
+/// which values were forced and where the search had to guess. Events from
+/// abandoned branches stay in the log, each closed by a `Backtrack`.
+pub fn solve_with_trace(grid: &Grid) -> (Option<Grid>, Vec<SolveEvent>) {
+    if !grid.is_valid() { return (None, Vec::new()); }
+    let mut solution = *grid;
+    update_candidates(&mut solution);
+    let mut events = Vec::new();
+
+    if solve_recursive_traced(&mut solution, 0, &mut events) {
+        (Some(solution), events)
+    } else {
+        (None, events)
+    }
+}
+
+/// Like `solve`, but with an `rng` tries digits in a random order at each
+/// branch, so repeated calls (e.g. on an empty grid) yield different,
+/// seed-reproducible solutions. Without one, digits go in ascending order and
+/// the result is the same canonical solution `solve` returns.
+pub fn solve_randomized(grid: &Grid, mut rng: Option<&mut SmallRng>) -> Option<Grid> {
+    if !grid.is_valid() { return None; } // Conflicting givens
+    let mut solution = *grid;
+    update_candidates(&mut solution);
+    
+    if solve_recursive(&mut solution, &mut rng) {
+        Some(solution)
+    } else {
+        None
+    }
+}
+
+/// `solve` over a batch, as one stable entry point for benchmarks.
+pub fn solve_many(puzzles: &[Grid]) -> Vec<Option<Grid>> {
+    puzzles.iter().map(solve).collect()
+}
+
+/// No sudoku with fewer clues has a unique solution (McGuire et al., 2012).
+pub const MIN_UNIQUE_CLUES: usize = 17;
+
+/// Exactly one solution. Grids below `MIN_UNIQUE_CLUES` (including the empty
+/// grid) are rejected without searching.
 pub fn is_unique(grid: &Grid) -> bool {
+    if grid.clue_count() < MIN_UNIQUE_CLUES || !grid.is_valid() { return false; }
     let mut g = *grid;
     update_candidates(&mut g);
     let mut count = 0;
-    count_solutions(&mut g, &mut count);
+    count_solutions(&mut g, &mut count, &mut None);
     count == 1
 }
 
+/// Search stack for `is_unique_with`, kept between calls so checking many
+/// puzzles grows it once instead of allocating per puzzle.
+#[derive(Debug, Default)]
+pub struct SolverScratch {
+    stack: Vec<Grid>, // Branches still to explore, deepest last
+}
+
+impl SolverScratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// `is_unique` on a caller-owned `SolverScratch`.
+pub fn is_unique_with(grid: &Grid, scratch: &mut SolverScratch) -> bool {
+    if grid.clue_count() < MIN_UNIQUE_CLUES || !grid.is_valid() { return false; }
+    let mut g = *grid;
+    update_candidates(&mut g);
+    count_solutions_with(g, scratch) == 1
+}
+
+/// `is_unique` for each puzzle string, in order, so JS can check a whole
+/// list in one call. One `SolverScratch` serves the whole batch.
+pub fn check_unique_batch(puzzles: &[String]) -> Vec<bool> {
+    let mut scratch = SolverScratch::new();
+    puzzles.iter().map(|p| is_unique_with(&Grid::from_string(p), &mut scratch)).collect()
+}
+
+/// A solution and whether it is the only one, from a single search that stops
+/// at the second solution. `None` if the grid has no solution. Below
+/// `MIN_UNIQUE_CLUES` this is just `solve`, reported as not unique.
+pub fn solve_unique(grid: &Grid) -> Option<(Grid, bool)> {
+    if grid.clue_count() < MIN_UNIQUE_CLUES { return solve(grid).map(|solution| (solution, false)); }
+    if !grid.is_valid() { return None; }
+    let mut g = *grid;
+    update_candidates(&mut g);
+    let mut count = 0;
+    let mut first = None;
+    count_solutions(&mut g, &mut count, &mut first);
+    first.map(|solution| (solution, count == 1))
+}
+
 pub fn check_uniqueness_after_removal(grid: &Grid, cell: usize, removed_val: u8) -> bool {
+    if grid.clue_count() < MIN_UNIQUE_CLUES { return false; }
     // We know 'grid' (with 'val' at 'cell') has 1 solution (the original full grid).
-    // We want to check if there is ANY solution where cell != removed_val.
-    // If we find one, then the puzzle is NOT unique (original solution + new solution).
-    // If we find none, then the puzzle IS unique (only original solution exists).
-    
+    // If there is ANY solution where cell != removed_val, the puzzle is NOT unique.
+    other_solution(grid, cell, removed_val).is_none()
+}
+
+/// A solution of `grid` (which has 0 at `cell`) with something other than
+/// `removed_val` at `cell`, i.e. a witness that removing that clue broke
+/// uniqueness. The generator uses it to pick a clue that restores it.
+pub fn other_solution(grid: &Grid, cell: usize, removed_val: u8) -> Option<Grid> {
     let mut g = *grid;
-    // The grid passed in has 0 at 'cell'.
-    // We want to forbid 'removed_val' at 'cell'.
-    
     // Update candidates for the whole grid first
     update_candidates(&mut g);
     
     // Now remove 'removed_val' from candidates of 'cell'
-    g.candidates[cell] &= !(1 << (removed_val - 1));
+    g.candidates[cell].remove(removed_val);
     
     // If no candidates left, then no other solution exists -> Unique
-    if g.candidates[cell] == 0 {
-        return true;
+    if g.candidates[cell].is_empty() {
+        return None;
     }
     
     // Try to find ONE solution
-    solve_recursive(&mut g) == false
+    if solve_recursive(&mut g, &mut None) { Some(g) } else { None }
+}
+
+/// Runs the logical techniques from `get_hint` until solved or stuck, returning
+/// the final grid and every hint applied along the way.
+pub fn solve_logically(grid: &Grid) -> (Grid, Vec<Hint>) {
+    let mut g = *grid;
+    update_candidates(&mut g);
+    let mut steps = Vec::new();
+
+    while !g.is_solved() {
+        match get_hint(&g) {
+            Some(hint) => {
+                apply_hint(&mut g, &hint);
+                steps.push(hint);
+            }
+            None => break,
+        }
+    }
+    (g, steps)
+}
+
+/// True if the human techniques in `get_hint` solve the grid without guessing.
+pub fn is_logically_solvable(grid: &Grid) -> bool {
+    let mut g = *grid;
+    update_candidates(&mut g);
+
+    while !g.is_solved() {
+        match get_hint(&g) {
+            Some(hint) => apply_hint(&mut g, &hint),
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Guesses a human needs on top of the `get_hint` techniques: whenever logic
+/// stalls, the cell with the fewest candidates is filled from the solution and
+/// counted as one branch point. 0 for logic-solvable or unsolvable grids.
+pub fn min_guess_depth(grid: &Grid) -> usize {
+    let solution = match solve(grid) {
+        Some(s) => s,
+        None => return 0,
+    };
+    let mut g = *grid;
+    update_candidates(&mut g);
+    let mut guesses = 0;
+
+    loop {
+        while let Some(hint) = get_hint(&g) {
+            apply_hint(&mut g, &hint);
+        }
+        if g.is_solved() { return guesses; }
+
+        let cell = (0..SIZE).filter(|&i| g.values[i] == 0).min_by_key(|&i| g.candidates[i].len()).unwrap();
+        let digit = solution.values[cell];
+        g.set_value(cell, digit);
+        update_candidates_after_move(&mut g, cell, digit);
+        guesses += 1;
+    }
+}
+
+/// Size of the smallest singles backdoor: cells which, filled from the
+/// solution, let naked and hidden singles finish the grid. A hardness
+/// measure independent of the technique ladder; 0 when singles alone solve.
+/// Sets are tried smallest first, so the cost grows with the open cells to
+/// the power of the answer; `None` if it exceeds `max` or the grid has no
+/// solution. Grids with several solutions are measured against `solve`'s.
+pub fn backdoor_size(grid: &Grid, max: usize) -> Option<usize> {
+    let solution = solve(grid)?;
+    let mut g = *grid;
+    update_candidates(&mut g);
+    propagate_singles(&mut g);
+    // Cells the singles already fill can never shrink a backdoor
+    let open: Vec<usize> = (0..SIZE).filter(|&i| g.values[i] == 0).collect();
+    (0..=max).find(|&size| backdoor_exists(&g, &solution, &open, size))
+}
+
+/// True if `size` more cells from `open` (all empty in `grid`) make singles
+/// solve it. `grid` is kept closed under singles, so once a cell fills itself
+/// any set including it is covered by a smaller one.
+fn backdoor_exists(grid: &Grid, solution: &Grid, open: &[usize], size: usize) -> bool {
+    if size == 0 {
+        return grid.is_solved();
+    }
+    for (i, &cell) in open.iter().enumerate() {
+        if grid.values[cell] != 0 { continue; }
+        let mut next = *grid;
+        let digit = solution.values[cell];
+        next.set_value(cell, digit);
+        update_candidates_after_move(&mut next, cell, digit);
+        propagate_singles(&mut next);
+        if backdoor_exists(&next, solution, &open[i + 1..], size - 1) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Diagnostic for branching: entry `k` counts empty cells with `k` candidates
+/// once candidates are computed and singles propagated. Weight at the high end
+/// means a wide search tree; any count at 0 means a contradiction.
+pub fn candidate_histogram(grid: &Grid) -> [usize; 10] {
+    let mut g = *grid;
+    update_candidates(&mut g);
+    propagate_singles(&mut g);
+
+    let mut histogram = [0; 10];
+    for i in 0..SIZE {
+        if g.values[i] == 0 {
+            histogram[g.candidates[i].len()] += 1;
+        }
+    }
+    histogram
+}
+
+/// Singles applied by `propagate_singles_counting`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SinglesApplied {
+    pub full_house: usize,
+    pub naked: usize,
+    pub hidden: usize,
+    pub order: [Option<&'static str>; 3], // Technique names in order of first use
+}
+
+impl SinglesApplied {
+    fn note(&mut self, technique: &'static str) {
+        match technique {
+            "full_house" => self.full_house += 1,
+            "naked_single" => self.naked += 1,
+            _ => self.hidden += 1,
+        }
+        if let Some(slot) = self.order.iter_mut().find(|t| t.is_none_or(|t| t == technique)) {
+            *slot = Some(technique);
+        }
+    }
 }
 
-fn count_solutions(grid: &mut Grid, count: &mut usize) {
+/// Applies naked and hidden singles until none remain, without building hints.
+/// Returns true if anything was placed.
+pub fn propagate_singles(grid: &mut Grid) -> bool {
+    let applied = propagate_singles_counting(grid);
+    applied.full_house + applied.naked + applied.hidden > 0
+}
+
+/// Same order as `get_hint` (the first full house by unit, else any naked
+/// single, else the first hidden single by unit), so ratings match running
+/// the singles through the full ladder; `examples/singles_fast_path.rs`
+/// checks that and times both.
+pub fn propagate_singles_counting(grid: &mut Grid) -> SinglesApplied {
+    let mut applied = SinglesApplied::default();
+
+    'outer: loop {
+        for unit in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()) {
+            let mut empty = unit.iter().copied().filter(|&cell| grid.values[cell] == 0);
+            let (Some(cell), None) = (empty.next(), empty.next()) else { continue };
+            let placed = unit.iter().fold(Candidates::NONE, |acc, &c| if c == cell { acc } else { acc | Candidates::from_digit(grid.values[c]) });
+            if let Some(digit) = (!placed).single().filter(|&d| grid.candidates[cell].contains(d)) {
+                grid.set_value(cell, digit);
+                update_candidates_after_move(grid, cell, digit);
+                applied.note("full_house");
+                continue 'outer;
+            }
+        }
+
+        for i in 0..SIZE {
+            if grid.values[i] == 0 && grid.candidates[i].len() == 1 {
+                let digit = grid.candidates[i].first().unwrap();
+                grid.set_value(i, digit);
+                update_candidates_after_move(grid, i, digit);
+                applied.note("naked_single");
+                continue 'outer;
+            }
+        }
+
+        for unit in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()) {
+            let mut placed = Candidates::NONE;
+            let mut seen_once = Candidates::NONE;
+            let mut seen_more = Candidates::NONE;
+            for &cell in unit.iter() {
+                if grid.values[cell] != 0 {
+                    placed.insert(grid.values[cell]);
+                } else {
+                    let c = grid.candidates[cell];
+                    seen_more |= seen_once & c;
+                    seen_once |= c;
+                }
+            }
+
+            // Lowest digit first
+            let Some(digit) = (seen_once & !seen_more & !placed).first() else { continue };
+            for &cell in unit.iter() {
+                if grid.values[cell] == 0 && grid.candidates[cell].contains(digit) {
+                    grid.set_value(cell, digit);
+                    update_candidates_after_move(grid, cell, digit);
+                    applied.note("hidden_single");
+                    continue 'outer;
+                }
+            }
+        }
+
+        return applied;
+    }
+}
+
+/// Counts solutions up to 2, keeping the first one found in `first`.
+fn count_solutions(grid: &mut Grid, count: &mut usize, first: &mut Option<Grid>) {
     if *count > 1 { return; }
     
     let mut min_candidates = 10;
@@ -54,7 +361,7 @@ fn count_solutions(grid: &mut Grid, count: &mut usize) {
     
     for i in 0..SIZE {
         if grid.values[i] == 0 {
-            let c = grid.candidates[i].count_ones();
+            let c = grid.candidates[i].len();
             if c == 0 { return; } // Invalid state
             if c < min_candidates {
                 min_candidates = c;
@@ -66,28 +373,71 @@ fn count_solutions(grid: &mut Grid, count: &mut usize) {
     
     if best_cell == SIZE {
         *count += 1;
+        if first.is_none() { *first = Some(*grid); }
         return;
     }
     
     let candidates = grid.candidates[best_cell];
-    for digit in 1..=9 {
-        if (candidates >> (digit - 1)) & 1 == 1 {
-            let mut next_grid = *grid;
+    for digit in candidates.iter() {
+        let mut next_grid = *grid;
+        next_grid.values[best_cell] = digit;
+        if update_candidates_after_move(&mut next_grid, best_cell, digit) {
+            count_solutions(&mut next_grid, count, first);
+        }
+    }
+}
+
+/// `count_solutions` without recursion: pending branches live on the
+/// scratch stack. Stops at 2.
+fn count_solutions_with(grid: Grid, scratch: &mut SolverScratch) -> usize {
+    scratch.stack.clear();
+    scratch.stack.push(grid);
+    let mut count = 0;
+
+    while let Some(grid) = scratch.stack.pop() {
+        let mut min_candidates = 10;
+        let mut best_cell = SIZE;
+        let mut dead = false;
+        for i in 0..SIZE {
+            if grid.values[i] == 0 {
+                let c = grid.candidates[i].len();
+                if c == 0 { dead = true; break; } // Invalid state
+                if c < min_candidates {
+                    min_candidates = c;
+                    best_cell = i;
+                    if c == 1 { break; }
+                }
+            }
+        }
+        if dead { continue; }
+
+        if best_cell == SIZE {
+            count += 1;
+            if count > 1 { break; }
+            continue;
+        }
+
+        // Pushed high to low so the lowest digit is explored first
+        for digit in (1..=9u8).rev().filter(|&d| grid.candidates[best_cell].contains(d)) {
+            let mut next_grid = grid;
             next_grid.values[best_cell] = digit;
             if update_candidates_after_move(&mut next_grid, best_cell, digit) {
-                count_solutions(&mut next_grid, count);
+                scratch.stack.push(next_grid);
             }
         }
     }
+    count
 }
 
-fn solve_recursive(grid: &mut Grid) -> bool {
+// Branches on the first cell (in index order) with the fewest candidates, trying
+// its digits ascending or, given an rng, shuffled
+fn solve_recursive(grid: &mut Grid, rng: &mut Option<&mut SmallRng>) -> bool {
     let mut min_candidates = 10;
     let mut best_cell = SIZE;
     
     for i in 0..SIZE {
         if grid.values[i] == 0 {
-            let c = grid.candidates[i].count_ones();
+            let c = grid.candidates[i].len();
             if c == 0 { return false; } // Invalid state
             if c < min_candidates {
                 min_candidates = c;
@@ -101,26 +451,74 @@ fn solve_recursive(grid: &mut Grid) -> bool {
         return true; // Solved
     }
     
-    let candidates = grid.candidates[best_cell];
-    for digit in 1..=9 {
-        if (candidates >> (digit - 1)) & 1 == 1 {
-            let mut next_grid = *grid;
-            next_grid.values[best_cell] = digit;
-            if update_candidates_after_move(&mut next_grid, best_cell, digit) {
-                if solve_recursive(&mut next_grid) {
-                    *grid = next_grid;
-                    return true;
-                }
+    let mut digits = [0u8; 9];
+    let mut count = 0;
+    for digit in grid.candidates[best_cell].iter() {
+        digits[count] = digit;
+        count += 1;
+    }
+    if let Some(rng) = rng.as_deref_mut() {
+        digits[..count].shuffle(rng);
+    }
+    for &digit in &digits[..count] {
+        let mut next_grid = *grid;
+        next_grid.values[best_cell] = digit;
+        if update_candidates_after_move(&mut next_grid, best_cell, digit) && solve_recursive(&mut next_grid, rng) {
+            *grid = next_grid;
+            return true;
+        }
+    }
+    
+    false
+}
+
+fn solve_recursive_traced(grid: &mut Grid, depth: usize, events: &mut Vec<SolveEvent>) -> bool {
+    let mut min_candidates = 10;
+    let mut best_cell = SIZE;
+    
+    for i in 0..SIZE {
+        if grid.values[i] == 0 {
+            let c = grid.candidates[i].len();
+            if c == 0 { return false; } // Invalid state
+            if c < min_candidates {
+                min_candidates = c;
+                best_cell = i;
+                if c == 1 { break; }
             }
         }
     }
     
+    if best_cell == SIZE {
+        return true; // Solved
+    }
+    
+    let candidates = grid.candidates[best_cell];
+    let forced = min_candidates == 1;
+    let next_depth = if forced { depth } else { depth + 1 };
+    for digit in candidates.iter() {
+        events.push(if forced {
+            SolveEvent::Single { cell: best_cell, digit }
+        } else {
+            SolveEvent::Guess { cell: best_cell, digit, depth: next_depth }
+        });
+
+        let mut next_grid = *grid;
+        next_grid.values[best_cell] = digit;
+        if update_candidates_after_move(&mut next_grid, best_cell, digit) && solve_recursive_traced(&mut next_grid, next_depth, events) {
+            *grid = next_grid;
+            return true;
+        }
+        if !forced {
+            events.push(SolveEvent::Backtrack { depth: next_depth });
+        }
+    }
+    
     false
 }
 
+/// Recomputes every candidate from `values`; see `Grid::reset_candidates`.
 pub fn update_candidates(grid: &mut Grid) {
-    // Reset candidates
-    grid.candidates = [0x1FF; SIZE];
+    grid.reset_candidates();
     
     // Apply constraints from existing values
     for i in 0..SIZE {
@@ -132,25 +530,111 @@ pub fn update_candidates(grid: &mut Grid) {
 }
 
 pub fn update_candidates_after_move(grid: &mut Grid, cell: usize, val: u8) -> bool {
-    let row = cell / 9;
-    let col = cell % 9;
-    let box_idx = (row / 3) * 3 + (col / 3);
-    
-    let mask = !(1 << (val - 1));
+    let row = row_of(cell);
+    let col = col_of(cell);
+    let box_idx = box_of(cell);
     
     for i in 0..SIZE {
-        if grid.values[i] == 0 {
-            let r = i / 9;
-            let c = i % 9;
-            let b = (r / 3) * 3 + (c / 3);
-            
-            if r == row || c == col || b == box_idx {
-                grid.candidates[i] &= mask;
-                if grid.candidates[i] == 0 {
-                    return false; // Conflict
-                }
+        if grid.values[i] == 0 && (row_of(i) == row || col_of(i) == col || box_of(i) == box_idx) {
+            grid.candidates[i].remove(val);
+            if grid.candidates[i].is_empty() {
+                return false; // Conflict
             }
         }
     }
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solve_rejects_duplicate_givens() {
+        let grid = Grid::from_string("5....5...");
+        assert!(!grid.is_valid());
+        assert!(solve(&grid).is_none());
+        assert!(!is_unique(&grid));
+        assert!(solve(&Grid::from_string("5")).is_some());
+        assert!(solve_unique(&grid).is_none());
+    }
+
+    #[test]
+    fn guess_depth_counts_only_stalls() {
+        let easy = Grid::from_string("...3....8129...6........59....93..4.7..1.......16.7.5..96..1..38...........56.1..");
+        assert_eq!(min_guess_depth(&easy), 0);
+        // Nothing to reason from, so the first move is a guess
+        assert!(min_guess_depth(&Grid::new()) > 0);
+    }
+
+    #[test]
+    fn backdoor_is_the_fewest_cells_singles_need() {
+        let easy = Grid::from_string("...3....8129...6........59....93..4.7..1.......16.7.5..96..1..38...........56.1..");
+        assert_eq!(backdoor_size(&easy, 0), Some(0));
+
+        let hard = Grid::from_string("1.......2.9.4...5...6...7...5.9.3.......7.......85..4.7.....6...3...9.8...2.....1");
+        assert_eq!(backdoor_size(&hard, 2), None);
+        assert_eq!(backdoor_size(&hard, 3), Some(3));
+
+        let conflict = Grid::from_string("11");
+        assert_eq!(backdoor_size(&conflict, 3), None);
+    }
+
+    #[test]
+    fn solve_unique_agrees_with_separate_calls() {
+        let puzzle = Grid::from_string("...3....8129...6........59....93..4.7..1.......16.7.5..96..1..38...........56.1..");
+        let (solution, unique) = solve_unique(&puzzle).unwrap();
+        assert!(unique);
+        assert_eq!(solution.to_string(), solve(&puzzle).unwrap().to_string());
+        assert_eq!(solve_unique(&Grid::new()).map(|(_, unique)| unique), Some(false));
+    }
+
+    #[test]
+    fn randomized_solve_is_seeded_or_canonical() {
+        use rand::SeedableRng;
+        let empty = Grid::new();
+        assert_eq!(solve_randomized(&empty, None).unwrap().to_string(), solve(&empty).unwrap().to_string());
+
+        let seeded = |seed| solve_randomized(&empty, Some(&mut SmallRng::seed_from_u64(seed))).unwrap().to_string();
+        assert_eq!(seeded(7), seeded(7));
+        assert_ne!(seeded(7), seeded(8));
+    }
+
+    #[test]
+    fn solves_hard_fixtures() {
+        let puzzles: Vec<Grid> = include_str!("../tests/fixtures/hard.txt")
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .map(Grid::from_string)
+            .collect();
+        assert!(puzzles.len() >= 9);
+        let mut scratch = SolverScratch::new();
+        for (puzzle, solution) in puzzles.iter().zip(solve_many(&puzzles)) {
+            let solution = solution.unwrap();
+            assert!(solution.is_solved_correctly());
+            assert!((0..SIZE).all(|i| puzzle.values[i] == 0 || puzzle.values[i] == solution.values[i]));
+            assert!(is_unique(puzzle));
+            assert!(is_unique_with(puzzle, &mut scratch));
+
+            // Blanking two clues gives a second solution; both paths must agree
+            let mut loosened = *puzzle;
+            for cell in (0..SIZE).filter(|&i| puzzle.values[i] != 0).take(2) {
+                loosened.values[cell] = 0;
+            }
+            assert_eq!(is_unique_with(&loosened, &mut scratch), is_unique(&loosened));
+        }
+    }
+
+    #[test]
+    fn sparse_grids_are_never_unique() {
+        assert!(!is_unique(&Grid::new()));
+        assert_eq!(check_unique_batch(&[".".repeat(81)]), vec![false]);
+        let (solution, unique) = solve_unique(&Grid::new()).unwrap();
+        assert!(solution.is_solved_correctly() && !unique);
+
+        // The hard fixtures include 17-clue puzzles, which must still pass
+        let seventeen = Grid::from_string("000000010400000000020000000000050407008000300001090000300400200050100000000806000");
+        assert_eq!(seventeen.clue_count(), MIN_UNIQUE_CLUES);
+        assert!(is_unique(&seventeen));
+    }
+}
@@ -1,7 +1,8 @@
 
-use crate::grid::{Grid, SIZE};
-use crate::utils::{ROWS, COLS, BOXES, get_peers};
-use std::collections::{HashSet, HashMap};
+use crate::grid::{Candidates, Grid, SIZE};
+use crate::utils::{ROWS, COLS, BOXES, get_peers, cell_name, box_of, col_of, row_of};
+use crate::solver::update_candidates_after_move;
+use bitflags::bitflags;
 
 #[derive(Debug, Clone)]
 pub struct Hint {
@@ -9,57 +10,347 @@ pub struct Hint {
     pub technique: &'static str,
     pub eliminations: Vec<(usize, u8)>, // (cell_idx, digit)
     pub placements: Vec<(usize, u8)>,   // (cell_idx, digit)
+    pub base_cells: Vec<usize>,         // Cells forming the pattern (pair, fish, chain); empty for singles
+    pub subtype: u8,                    // Variant within the technique (e.g. UR type), 0 if none
+    pub search_cost: u32,               // Spots inspected before the pattern showed; 0 where not estimated
 }
 
-pub fn get_hint(grid: &Grid) -> Option<Hint> {
+impl Hint {
+    /// How much progress the hint makes, for ranking equally easy hints: a
+    /// placement counts 9 (it settles a cell and clears its peers), each
+    /// elimination 1.
+    pub fn impact(&self) -> usize {
+        self.placements.len() * 9 + self.eliminations.len()
+    }
+
+    /// SudokuWiki-style log line, for comparing against its solver:
+    ///
+    /// `<Label>: <digits> in <base cells> <moves>`, e.g.
+    /// `Naked Pair: 3,7 in r1c2,r1c5 removing 3,7 from r1c8`.
+    ///
+    /// `<digits>` are the distinct digits placed or removed, ascending, and the
+    /// `<digits> in <base cells>` part is left out when `base_cells` is empty
+    /// (singles). `<moves>` is `placing <d> in <cell>` per placement, then
+    /// `removing <digits> from <cells>` per distinct set of digits removed from
+    /// a cell, in first-seen order, all joined by `", "`. Cells are `r#c#`.
+    pub fn to_notation(&self) -> String {
+        let join = |items: Vec<String>| items.join(",");
+        let digits_of = |ds: &[u8]| join(ds.iter().map(|d| d.to_string()).collect());
+
+        let mut moves: Vec<String> = self.placements.iter().map(|&(cell, d)| format!("placing {} in {}", d, cell_name(cell))).collect();
+
+        // Group cells by the digits they lose
+        let mut removed: Vec<(usize, Vec<u8>)> = Vec::new();
+        for &(cell, d) in &self.eliminations {
+            match removed.iter_mut().find(|(c, _)| *c == cell) {
+                Some((_, ds)) => ds.push(d),
+                None => removed.push((cell, vec![d])),
+            }
+        }
+        let mut groups: Vec<(Vec<u8>, Vec<usize>)> = Vec::new();
+        for (cell, mut ds) in removed {
+            ds.sort_unstable();
+            match groups.iter_mut().find(|(g, _)| *g == ds) {
+                Some((_, cells)) => cells.push(cell),
+                None => groups.push((ds, vec![cell])),
+            }
+        }
+        for (ds, cells) in &groups {
+            moves.push(format!("removing {} from {}", digits_of(ds), join(cells.iter().map(|&c| cell_name(c)).collect())));
+        }
+
+        let label = technique_label(self.technique);
+        if self.base_cells.is_empty() {
+            return format!("{}: {}", label, moves.join(", "));
+        }
+        let mut digits: Vec<u8> = self.placements.iter().chain(&self.eliminations).map(|&(_, d)| d).collect();
+        digits.sort_unstable();
+        digits.dedup();
+        let base = join(self.base_cells.iter().map(|&c| cell_name(c)).collect());
+        format!("{}: {} in {} {}", label, digits_of(&digits), base, moves.join(", "))
+    }
+}
+
+/// Display name of a technique as SudokuWiki writes it.
+fn technique_label(technique: &str) -> &'static str {
+    match technique {
+        "full_house" => "Full House",
+        "naked_single" => "Naked Single",
+        "hidden_single" => "Hidden Single",
+        "naked_pairs" => "Naked Pair",
+        "pointing_pairs" => "Pointing Pair",
+        "pointing_triples" => "Pointing Triple",
+        "box_line_reduction" | "box_line_triples" => "Box/Line Reduction",
+        "hidden_pairs" => "Hidden Pair",
+        "naked_triples" => "Naked Triple",
+        "hidden_triples" => "Hidden Triple",
+        "naked_quads" => "Naked Quad",
+        "hidden_quads" => "Hidden Quad",
+        "x_wing" => "X-Wing",
+        "skyscraper" => "Skyscraper",
+        "two_string_kite" => "2-String Kite",
+        "turbot_fish" => "Turbot Fish",
+        "empty_rectangle" => "Empty Rectangle",
+        "finned_x_wing" => "Finned X-Wing",
+        "sashimi_x_wing" => "Sashimi X-Wing",
+        "y_wing" => "Y-Wing",
+        "unique_rectangle" => "Unique Rectangle",
+        "simple_coloring" => "Simple Colouring",
+        "avoidable_rectangle" => "Avoidable Rectangle",
+        "grouped_wing" => "Grouped Wing",
+        "transported_wing" => "Transported Wing",
+        "x_cycle" => "X-Cycle",
+        "aic" => "Alternating Inference Chain",
+        "death_blossom" => "Death Blossom",
+        "cell_forcing_chain" => "Cell Forcing Chain",
+        _ => "Unknown Technique",
+    }
+}
+
+/// Every technique a detector can report, with its difficulty weight, in
+/// ladder order. Detectors read their difficulty from here.
+pub const TECHNIQUES: &[(&str, f32)] = &[
+    ("full_house", 0.5),
+    ("naked_single", 1.0),
+    ("hidden_single", 7.0),
+    ("naked_pairs", 9.0),
+    ("pointing_pairs", 12.0),
+    ("pointing_triples", 13.0),
+    ("box_line_reduction", 14.0),
+    ("box_line_triples", 15.0),
+    ("hidden_pairs", 18.0),
+    ("naked_triples", 22.0),
+    ("hidden_triples", 28.0),
+    ("naked_quads", 35.0),
+    ("hidden_quads", 42.0),
+    ("x_wing", 46.0),
+    ("skyscraper", 46.5),
+    ("two_string_kite", 47.0),
+    ("turbot_fish", 47.5),
+    ("empty_rectangle", 47.5),
+    ("finned_x_wing", 48.0),
+    ("sashimi_x_wing", 49.0),
+    ("y_wing", 50.0),
+    ("unique_rectangle", 52.0),
+    ("simple_coloring", 54.0),
+    ("avoidable_rectangle", 56.0),
+    ("grouped_wing", 58.0),
+    ("transported_wing", 62.0),
+    ("x_cycle", 66.0),
+    ("aic", 70.0),
+    ("death_blossom", 75.0),
+    ("cell_forcing_chain", 80.0),
+];
+
+pub fn all_techniques() -> &'static [(&'static str, f32)] {
+    TECHNIQUES
+}
+
+pub fn technique_difficulty(technique: &str) -> f32 {
+    TECHNIQUES.iter().find(|(name, _)| *name == technique).map(|&(_, d)| d).unwrap_or(0.0)
+}
+
+bitflags! {
+    /// Which techniques a solve may use: bit `i` is `TECHNIQUES[i]`, with the
+    /// ladder's usual cut points as named groups.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct TechniqueSet: u32 {
+        const FULL_HOUSE = 1 << 0;
+        const NAKED_SINGLE = 1 << 1;
+        const HIDDEN_SINGLE = 1 << 2;
+        const NAKED_PAIRS = 1 << 3;
+        const POINTING_PAIRS = 1 << 4;
+        const POINTING_TRIPLES = 1 << 5;
+        const BOX_LINE_REDUCTION = 1 << 6;
+        const BOX_LINE_TRIPLES = 1 << 7;
+        const HIDDEN_PAIRS = 1 << 8;
+        const NAKED_TRIPLES = 1 << 9;
+        const HIDDEN_TRIPLES = 1 << 10;
+        const NAKED_QUADS = 1 << 11;
+        const HIDDEN_QUADS = 1 << 12;
+        const X_WING = 1 << 13;
+        const SKYSCRAPER = 1 << 14;
+        const TWO_STRING_KITE = 1 << 15;
+        const TURBOT_FISH = 1 << 16;
+        const EMPTY_RECTANGLE = 1 << 17;
+        const FINNED_X_WING = 1 << 18;
+        const SASHIMI_X_WING = 1 << 19;
+        const Y_WING = 1 << 20;
+        const UNIQUE_RECTANGLE = 1 << 21;
+        const SIMPLE_COLORING = 1 << 22;
+        const AVOIDABLE_RECTANGLE = 1 << 23;
+        const GROUPED_WING = 1 << 24;
+        const TRANSPORTED_WING = 1 << 25;
+        const X_CYCLE = 1 << 26;
+        const AIC = 1 << 27;
+        const DEATH_BLOSSOM = 1 << 28;
+        const CELL_FORCING_CHAIN = 1 << 29;
+
+        const SINGLES = Self::FULL_HOUSE.bits() | Self::NAKED_SINGLE.bits() | Self::HIDDEN_SINGLE.bits();
+        /// Everything `get_basic_hint` runs.
+        const BASIC = Self::SINGLES.bits() | Self::NAKED_PAIRS.bits() | Self::POINTING_PAIRS.bits()
+            | Self::POINTING_TRIPLES.bits() | Self::BOX_LINE_REDUCTION.bits() | Self::BOX_LINE_TRIPLES.bits();
+        /// Basic plus the larger naked and hidden subsets.
+        const INTERMEDIATE = Self::BASIC.bits() | Self::HIDDEN_PAIRS.bits() | Self::NAKED_TRIPLES.bits()
+            | Self::HIDDEN_TRIPLES.bits() | Self::NAKED_QUADS.bits() | Self::HIDDEN_QUADS.bits();
+        const ALL = (1 << 30) - 1;
+    }
+}
+
+impl TechniqueSet {
+    /// Whether the `TECHNIQUES` name `technique` is in the set.
+    fn allows(self, technique: &str) -> bool {
+        Self::from_technique(technique).is_some_and(|t| self.contains(t))
+    }
+
+    /// Flag for a `TECHNIQUES` name, `None` if unknown.
+    pub fn from_technique(name: &str) -> Option<Self> {
+        TECHNIQUES.iter().position(|&(t, _)| t == name).map(|i| Self::from_bits_retain(1 << i))
+    }
+}
+
+/// Detectors covering several techniques only report those in the set.
+type Detector = fn(&Grid, TechniqueSet) -> Option<Hint>;
+
+/// `get_hint` order. Each detector is tagged with every technique it can
+/// report, since some (turbot fish, locked candidates) cover several.
+const LADDER: &[(TechniqueSet, Detector)] = &[
     // Stage 2: Trivial/Getting Started
-    if let Some(h) = detect_naked_single(grid) { return Some(h); }
-    if let Some(h) = detect_hidden_single(grid) { return Some(h); }
-    
+    (TechniqueSet::FULL_HOUSE, |g, _| detect_full_house(g)),
+    (TechniqueSet::NAKED_SINGLE, |g, _| detect_naked_single(g)),
+    (TechniqueSet::HIDDEN_SINGLE, |g, _| detect_hidden_single(g)),
     // Stage 3: Basic Elimination
-    if let Some(h) = detect_naked_subset(grid, 2) { return Some(h); } // Naked Pair
-    if let Some(h) = detect_pointing_pairs(grid) { return Some(h); }
-    if let Some(h) = detect_box_line_reduction(grid) { return Some(h); }
-    if let Some(h) = detect_hidden_subset(grid, 2) { return Some(h); } // Hidden Pair
-    if let Some(h) = detect_naked_subset(grid, 3) { return Some(h); } // Naked Triple
-    if let Some(h) = detect_hidden_subset(grid, 3) { return Some(h); } // Hidden Triple
-    
+    (TechniqueSet::NAKED_PAIRS, |g, _| detect_naked_subset(g, 2)),
+    (TechniqueSet::POINTING_PAIRS.union(TechniqueSet::POINTING_TRIPLES), detect_pointing_pairs),
+    (TechniqueSet::BOX_LINE_REDUCTION.union(TechniqueSet::BOX_LINE_TRIPLES), detect_box_line_reduction),
+    (TechniqueSet::HIDDEN_PAIRS, |g, _| detect_hidden_subset(g, 2)),
+    (TechniqueSet::NAKED_TRIPLES, |g, _| detect_naked_subset(g, 3)),
+    (TechniqueSet::HIDDEN_TRIPLES, |g, _| detect_hidden_subset(g, 3)),
     // Stage 4: Advanced Elimination
-    if let Some(h) = detect_naked_subset(grid, 4) { return Some(h); } // Naked Quad
-    if let Some(h) = detect_hidden_subset(grid, 4) { return Some(h); } // Hidden Quad
-    
+    (TechniqueSet::NAKED_QUADS, |g, _| detect_naked_subset(g, 4)),
+    (TechniqueSet::HIDDEN_QUADS, |g, _| detect_hidden_subset(g, 4)),
     // Stage 5: Fish and Wings
-    if let Some(h) = detect_x_wing(grid) { return Some(h); }
-    if let Some(h) = detect_y_wing(grid) { return Some(h); }
-    
+    (TechniqueSet::X_WING, |g, _| detect_x_wing(g)),
+    (
+        TechniqueSet::SKYSCRAPER
+            .union(TechniqueSet::TWO_STRING_KITE)
+            .union(TechniqueSet::TURBOT_FISH)
+            .union(TechniqueSet::EMPTY_RECTANGLE),
+        detect_turbot_fish,
+    ),
+    (TechniqueSet::FINNED_X_WING.union(TechniqueSet::SASHIMI_X_WING), detect_finned_x_wing),
+    (TechniqueSet::Y_WING, |g, _| detect_y_wing(g)),
+    (TechniqueSet::UNIQUE_RECTANGLE, |g, _| detect_unique_rectangle(g)),
+    (TechniqueSet::AVOIDABLE_RECTANGLE, |g, _| detect_avoidable_rectangle(g)),
     // Stage 6: Intermediate Patterns
-    if let Some(h) = detect_simple_coloring(grid) { return Some(h); }
-    
-    None
+    (TechniqueSet::SIMPLE_COLORING, |g, _| detect_simple_coloring(g)),
+    (TechniqueSet::GROUPED_WING, |g, _| detect_grouped_wing(g)),
+    (TechniqueSet::TRANSPORTED_WING, |g, _| detect_transported_wing(g, TRANSPORT_MAX_LINKS)),
+    (TechniqueSet::X_CYCLE, |g, _| detect_x_cycle(g)),
+    (TechniqueSet::AIC, |g, _| detect_aic(g, AIC_MAX_NODES)),
+    // Stage 7: Almost Locked Sets
+    (TechniqueSet::DEATH_BLOSSOM, |g, _| detect_death_blossom(g)),
+    // Stage 8: Forcing Chains
+    (TechniqueSet::CELL_FORCING_CHAIN, |g, _| detect_cell_forcing_chain(g, 20)),
+];
+
+/// The cheap end of the ladder: singles, naked pairs and locked candidates,
+/// in `get_hint` order.
+pub fn get_basic_hint(grid: &Grid) -> Option<Hint> {
+    get_hint_with(grid, TechniqueSet::BASIC)
 }
 
-fn get_candidates(grid: &Grid, cell: usize) -> Vec<u8> {
-    let mut res = Vec::with_capacity(9);
-    let mask = grid.candidates[cell];
-    for d in 1..=9 {
-        if (mask >> (d - 1)) & 1 == 1 {
-            res.push(d as u8);
+/// `get_hint` restricted to `set`. A detector covering several techniques
+/// runs if any of them is enabled and looks only for those, so e.g. a
+/// pointing triple is still found when only triples are enabled.
+pub fn get_hint_with(grid: &Grid, set: TechniqueSet) -> Option<Hint> {
+    LADDER
+        .iter()
+        .filter(|(flags, _)| set.intersects(*flags))
+        .find_map(|(_, detect)| detect(grid, set))
+}
+
+/// `get_hint` with the techniques tried in `order` instead of ladder order,
+/// for modelling solvers that look for, say, fish before subsets. Only the
+/// listed techniques are tried and the hint must be the one asked for, as in
+/// `get_hint_with`; unknown names are skipped.
+pub fn get_hint_ordered(grid: &Grid, order: &[&str]) -> Option<Hint> {
+    order.iter().find_map(|&name| detect_technique(grid, name).filter(|h| h.technique == name))
+}
+
+/// Next hint for a player's board, trusting their pencil marks: a digit they
+/// crossed out stays out. `get_hint` never recomputes candidates itself (every
+/// detector only reads the grid), so this is `get_hint` on exactly these masks.
+/// Masks of filled cells are ignored. Every filled cell counts as a given, so
+/// no Avoidable Rectangle is offered.
+pub fn get_hint_from_candidates(values: &[u8; SIZE], candidates: &[Candidates; SIZE]) -> Option<Hint> {
+    let grid = Grid { values: *values, candidates: *candidates, givens: values.map(|v| v != 0) };
+    get_hint(&grid)
+}
+
+/// First empty cell with no candidates left, i.e. proof the board is broken.
+/// `get_hint` returns `None` both for such a board and for a valid one beyond
+/// the ladder; check this first to tell the two apart.
+pub fn find_contradiction(grid: &Grid) -> Option<usize> {
+    (0..SIZE).find(|&i| grid.values[i] == 0 && grid.candidates[i].is_empty())
+}
+
+/// Next logical step, or `None` when stuck. See `find_contradiction` for
+/// telling a broken board apart from a hard one.
+pub fn get_hint(grid: &Grid) -> Option<Hint> {
+    get_hint_with(grid, TechniqueSet::ALL)
+}
+
+/// Full House: the last empty cell of a unit takes the one digit it is missing.
+/// A special case of both singles, reported separately for tutors.
+fn detect_full_house(grid: &Grid) -> Option<Hint> {
+    ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()).find_map(|unit| full_house_in(grid, unit))
+}
+
+fn full_house_in(grid: &Grid, unit: &[usize; 9]) -> Option<Hint> {
+    let mut empty = unit.iter().copied().filter(|&cell| grid.values[cell] == 0);
+    let (Some(cell), None) = (empty.next(), empty.next()) else { return None };
+
+    let placed = unit.iter().fold(Candidates::NONE, |acc, &c| if c == cell { acc } else { acc | Candidates::from_digit(grid.values[c]) });
+    let digit = (!placed).single().filter(|&d| grid.candidates[cell].contains(d))?;
+    Some(Hint {
+        difficulty: technique_difficulty("full_house"),
+        technique: "full_house",
+        eliminations: vec![],
+        placements: vec![(cell, digit)],
+        base_cells: vec![],
+        subtype: 0,
+        search_cost: 0,
+    })
+}
+
+/// Every full house, once per cell even when it closes several units.
+fn detect_full_houses_all(grid: &Grid) -> Vec<Hint> {
+    let mut hints: Vec<Hint> = Vec::new();
+    for hint in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()).filter_map(|unit| full_house_in(grid, unit)) {
+        if !hints.iter().any(|h| h.placements[0] == hint.placements[0]) {
+            hints.push(hint);
         }
     }
-    res
+    hints
 }
 
 fn detect_naked_single(grid: &Grid) -> Option<Hint> {
+    let mut inspected = 0;
     for i in 0..SIZE {
         if grid.values[i] == 0 {
+            inspected += 1;
             let mask = grid.candidates[i];
-            if mask.count_ones() == 1 {
-                let digit = mask.trailing_zeros() as u8 + 1;
+            if mask.len() == 1 {
+                let digit = mask.first().unwrap();
                 return Some(Hint {
-                    difficulty: 1.0,
+                    difficulty: technique_difficulty("naked_single"),
                     technique: "naked_single",
                     eliminations: vec![],
                     placements: vec![(i, digit)],
+                    base_cells: vec![],
+                    subtype: 0,
+                    search_cost: inspected,
                 });
             }
         }
@@ -67,27 +358,41 @@ fn detect_naked_single(grid: &Grid) -> Option<Hint> {
     None
 }
 
+/// `subtype` of a hidden single found in a row; a column is one more and a
+/// box two more. Rows are searched first, so a cell that is hidden in several
+/// units reports the earliest.
+pub const HIDDEN_IN_ROW: u8 = 1;
+pub const HIDDEN_IN_COL: u8 = 2;
+pub const HIDDEN_IN_BOX: u8 = 3;
+
+/// Hidden single: the only cell in a unit that can take a digit. The hint's
+/// `subtype` says which kind of unit it was found in, see `HIDDEN_IN_ROW`.
 fn detect_hidden_single(grid: &Grid) -> Option<Hint> {
-    for unit in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()) {
+    let mut inspected = 0;
+    for (u, unit) in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()).enumerate() {
         for d in 1..=9 {
+            // Already placed in this unit: any remaining candidate bits are stale
+            if unit.iter().any(|&cell| grid.values[cell] == d) { continue; }
+            inspected += 1;
+
             let mut count = 0;
             let mut last_pos = 0;
             for &cell in unit.iter() {
-                if grid.values[cell] == 0 && (grid.candidates[cell] >> (d - 1)) & 1 == 1 {
+                if grid.values[cell] == 0 && grid.candidates[cell].contains(d) {
                     count += 1;
                     last_pos = cell;
-                } else if grid.values[cell] == d as u8 {
-                    count = 0; // Already placed
-                    break;
                 }
             }
             
             if count == 1 {
                 return Some(Hint {
-                    difficulty: 7.0,
+                    difficulty: technique_difficulty("hidden_single"),
                     technique: "hidden_single",
                     eliminations: vec![],
-                    placements: vec![(last_pos, d as u8)],
+                    placements: vec![(last_pos, d)],
+                    base_cells: vec![],
+                    subtype: HIDDEN_IN_ROW + (u / 9) as u8,
+                    search_cost: inspected,
                 });
             }
         }
@@ -95,19 +400,196 @@ fn detect_hidden_single(grid: &Grid) -> Option<Hint> {
     None
 }
 
-fn detect_naked_subset(grid: &Grid, size: usize) -> Option<Hint> {
-    let difficulty = match size {
-        2 => 9.0,
-        3 => 22.0,
-        4 => 35.0,
-        _ => 0.0,
+/// Runs only the detector for `name` (any name from `TECHNIQUES`). Detectors
+/// that report several names, like pointing pairs/triples, look for just that one.
+/// `None` if the name is unknown or the technique doesn't apply.
+pub fn detect_technique(grid: &Grid, name: &str) -> Option<Hint> {
+    match name {
+        "full_house" => detect_full_house(grid),
+        "naked_single" => detect_naked_single(grid),
+        "hidden_single" => detect_hidden_single(grid),
+        "naked_pairs" => detect_naked_subset(grid, 2),
+        "naked_triples" => detect_naked_subset(grid, 3),
+        "naked_quads" => detect_naked_subset(grid, 4),
+        "hidden_pairs" => detect_hidden_subset(grid, 2),
+        "hidden_triples" => detect_hidden_subset(grid, 3),
+        "hidden_quads" => detect_hidden_subset(grid, 4),
+        "pointing_pairs" | "pointing_triples" => detect_pointing_pairs(grid, TechniqueSet::from_technique(name)?),
+        "box_line_reduction" | "box_line_triples" => detect_box_line_reduction(grid, TechniqueSet::from_technique(name)?),
+        "x_wing" => detect_x_wing(grid),
+        "skyscraper" | "two_string_kite" | "turbot_fish" | "empty_rectangle" => {
+            detect_turbot_fish(grid, TechniqueSet::from_technique(name)?)
+        }
+        "finned_x_wing" | "sashimi_x_wing" => detect_finned_x_wing(grid, TechniqueSet::from_technique(name)?),
+        "y_wing" => detect_y_wing(grid),
+        "unique_rectangle" => detect_unique_rectangle(grid),
+        "simple_coloring" => detect_simple_coloring(grid),
+        "avoidable_rectangle" => detect_avoidable_rectangle(grid),
+        "grouped_wing" => detect_grouped_wing(grid),
+        "transported_wing" => detect_transported_wing(grid, TRANSPORT_MAX_LINKS),
+        "x_cycle" => detect_x_cycle(grid),
+        "aic" => detect_aic(grid, AIC_MAX_NODES),
+        "death_blossom" => detect_death_blossom(grid),
+        "cell_forcing_chain" => detect_cell_forcing_chain(grid, 20),
+        _ => None,
+    }
+}
+
+/// Every hint the cheapest applicable technique can find in this grid state,
+/// so a full round (e.g. all naked singles) can be shown or applied at once.
+/// Techniques `detect_technique_all` covers are expanded fully; others yield
+/// their first hint.
+pub fn get_all_hints_of_cheapest(grid: &Grid) -> Vec<Hint> {
+    let first = match get_hint(grid) {
+        Some(h) => h,
+        None => return vec![],
     };
+    match first.technique {
+        "full_house" | "naked_single" | "hidden_single" | "naked_pairs" | "pointing_pairs" | "pointing_triples" => {
+            detect_technique_all(grid, first.technique)
+        }
+        _ => vec![first],
+    }
+}
+
+/// Empty cells among `unit_cells` that still list `digit`, in the given order.
+/// Pass a row, column, box or `cells_seeing` set to highlight where a digit
+/// can go.
+pub fn cells_with_candidate(grid: &Grid, unit_cells: &[usize], digit: u8) -> Vec<usize> {
+    unit_cells.iter().copied().filter(|&c| grid.values[c] == 0 && grid.candidates[c].contains(digit)).collect()
+}
+
+/// Cheapest single that places a digit in `cell`: full house, then naked,
+/// then hidden single. `None` if the cell is filled or no single reaches it
+/// yet, even when other cells have moves.
+pub fn single_for_cell(grid: &Grid, cell: usize) -> Option<Hint> {
+    if grid.values[cell] != 0 { return None; }
+
+    let full_house = [&ROWS[row_of(cell)], &COLS[col_of(cell)], &BOXES[box_of(cell)]]
+        .into_iter()
+        .any(|unit| unit.iter().all(|&c| c == cell || grid.values[c] != 0));
+    if let (true, Some(digit)) = (full_house, grid.candidates[cell].single()) {
+        return Some(Hint {
+            difficulty: technique_difficulty("full_house"),
+            technique: "full_house",
+            eliminations: vec![],
+            placements: vec![(cell, digit)],
+            base_cells: vec![],
+            subtype: 0,
+            search_cost: 0,
+        });
+    }
+    detect_naked_singles_all(grid)
+        .into_iter()
+        .chain(detect_hidden_singles_all(grid))
+        .find(|h| h.placements[0].0 == cell)
+}
+
+/// Every finding of one technique in the current state, for animated
+/// playback: all of them hold at once, so they can be applied together
+/// without rescanning. Covers full houses, singles, naked pairs and pointing
+/// (pairs and triples together); other names give at most `detect_technique`'s one hint.
+pub fn detect_technique_all(grid: &Grid, name: &str) -> Vec<Hint> {
+    match name {
+        "full_house" => detect_full_houses_all(grid),
+        "naked_single" => detect_naked_singles_all(grid),
+        "hidden_single" => detect_hidden_singles_all(grid),
+        "naked_pairs" => detect_naked_pairs_all(grid),
+        "pointing_pairs" | "pointing_triples" => detect_pointing_all(grid),
+        _ => detect_technique(grid, name).into_iter().collect(),
+    }
+}
+
+fn detect_naked_singles_all(grid: &Grid) -> Vec<Hint> {
+    let mut hints = Vec::new();
+    for i in 0..SIZE {
+        if grid.values[i] == 0 && grid.candidates[i].len() == 1 {
+            let digit = grid.candidates[i].first().unwrap();
+            hints.push(Hint {
+                difficulty: technique_difficulty("naked_single"),
+                technique: "naked_single",
+                eliminations: vec![],
+                placements: vec![(i, digit)],
+                base_cells: vec![],
+                subtype: 0,
+                search_cost: 0,
+            });
+        }
+    }
+    hints
+}
+
+fn detect_hidden_singles_all(grid: &Grid) -> Vec<Hint> {
+    let mut hints: Vec<Hint> = Vec::new();
+    for (u, unit) in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()).enumerate() {
+        for d in 1..=9 {
+            if unit.iter().any(|&cell| grid.values[cell] == d) { continue; }
+
+            let mut count = 0;
+            let mut last_pos = 0;
+            for &cell in unit.iter() {
+                if grid.values[cell] == 0 && grid.candidates[cell].contains(d) {
+                    count += 1;
+                    last_pos = cell;
+                }
+            }
+
+            // The same cell can be a hidden single in its row, column and box
+            if count == 1 && !hints.iter().any(|h| h.placements[0] == (last_pos, d)) {
+                hints.push(Hint {
+                    difficulty: technique_difficulty("hidden_single"),
+                    technique: "hidden_single",
+                    eliminations: vec![],
+                    placements: vec![(last_pos, d)],
+                    base_cells: vec![],
+                    subtype: HIDDEN_IN_ROW + (u / 9) as u8,
+                    search_cost: 0,
+                });
+            }
+        }
+    }
+    hints
+}
+
+fn detect_naked_pairs_all(grid: &Grid) -> Vec<Hint> {
+    let mut hints: Vec<Hint> = Vec::new();
+    for unit in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()) {
+        for i in 0..9 {
+            for j in i + 1..9 {
+                let (c1, c2) = (unit[i], unit[j]);
+                if grid.values[c1] != 0 || grid.values[c2] != 0 { continue; }
+                let union_candidates = grid.candidates[c1] | grid.candidates[c2];
+                if union_candidates.len() != 2 { continue; }
+
+                // A locked pair is met again in its second unit, already fully cleared
+                if hints.iter().any(|h| h.base_cells == [c1, c2]) { continue; }
+                let (eliminations, subtype) = subset_eliminations(grid, &[c1, c2], union_candidates);
+                if !eliminations.is_empty() {
+                    hints.push(Hint {
+                        difficulty: technique_difficulty("naked_pairs"),
+                        technique: "naked_pairs",
+                        eliminations,
+                        placements: vec![],
+                        base_cells: vec![c1, c2],
+                        subtype,
+                        search_cost: 0,
+                    });
+                }
+            }
+        }
+    }
+    hints
+}
+
+fn detect_naked_subset(grid: &Grid, size: usize) -> Option<Hint> {
     let technique = match size {
         2 => "naked_pairs",
         3 => "naked_triples",
         4 => "naked_quads",
         _ => "",
     };
+    let difficulty = technique_difficulty(technique);
+    let mut inspected = 0;
 
     for unit in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()) {
         let mut empty_cells = [0usize; 9];
@@ -129,24 +611,13 @@ fn detect_naked_subset(grid: &Grid, size: usize) -> Option<Hint> {
                     let c2 = empty_cells[j];
                     let union_candidates = grid.candidates[c1] | grid.candidates[c2];
                     
-                    if union_candidates.count_ones() == 2 {
+                    inspected += 1;
+                    
+                    if union_candidates.len() == 2 {
                         // Found naked pair
-                        let mut eliminations = Vec::new();
-                        for k in 0..count {
-                            let cell = empty_cells[k];
-                            if cell != c1 && cell != c2 {
-                                let common = grid.candidates[cell] & union_candidates;
-                                if common != 0 {
-                                    for d in 1..=9 {
-                                        if (common >> (d - 1)) & 1 == 1 {
-                                            eliminations.push((cell, d as u8));
-                                        }
-                                    }
-                                }
-                            }
-                        }
+                        let (eliminations, subtype) = subset_eliminations(grid, &[c1, c2], union_candidates);
                         if !eliminations.is_empty() {
-                            return Some(Hint { difficulty, technique, eliminations, placements: vec![] });
+                            return Some(Hint { difficulty, technique, eliminations, placements: vec![], base_cells: vec![c1, c2], subtype, search_cost: inspected });
                         }
                     }
                 }
@@ -160,24 +631,13 @@ fn detect_naked_subset(grid: &Grid, size: usize) -> Option<Hint> {
                         let c3 = empty_cells[k];
                         let union_candidates = grid.candidates[c1] | grid.candidates[c2] | grid.candidates[c3];
                         
-                        if union_candidates.count_ones() == 3 {
+                        inspected += 1;
+                        
+                        if union_candidates.len() == 3 {
                             // Found naked triple
-                            let mut eliminations = Vec::new();
-                            for l in 0..count {
-                                let cell = empty_cells[l];
-                                if cell != c1 && cell != c2 && cell != c3 {
-                                    let common = grid.candidates[cell] & union_candidates;
-                                    if common != 0 {
-                                        for d in 1..=9 {
-                                            if (common >> (d - 1)) & 1 == 1 {
-                                                eliminations.push((cell, d as u8));
-                                            }
-                                        }
-                                    }
-                                }
-                            }
+                            let (eliminations, subtype) = subset_eliminations(grid, &[c1, c2, c3], union_candidates);
                             if !eliminations.is_empty() {
-                                return Some(Hint { difficulty, technique, eliminations, placements: vec![] });
+                                return Some(Hint { difficulty, technique, eliminations, placements: vec![], base_cells: vec![c1, c2, c3], subtype, search_cost: inspected });
                             }
                         }
                     }
@@ -194,24 +654,13 @@ fn detect_naked_subset(grid: &Grid, size: usize) -> Option<Hint> {
                             let c4 = empty_cells[l];
                             let union_candidates = grid.candidates[c1] | grid.candidates[c2] | grid.candidates[c3] | grid.candidates[c4];
                             
-                            if union_candidates.count_ones() == 4 {
+                            inspected += 1;
+                            
+                            if union_candidates.len() == 4 {
                                 // Found naked quad
-                                let mut eliminations = Vec::new();
-                                for m in 0..count {
-                                    let cell = empty_cells[m];
-                                    if cell != c1 && cell != c2 && cell != c3 && cell != c4 {
-                                        let common = grid.candidates[cell] & union_candidates;
-                                        if common != 0 {
-                                            for d in 1..=9 {
-                                                if (common >> (d - 1)) & 1 == 1 {
-                                                    eliminations.push((cell, d as u8));
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
+                                let (eliminations, subtype) = subset_eliminations(grid, &[c1, c2, c3, c4], union_candidates);
                                 if !eliminations.is_empty() {
-                                    return Some(Hint { difficulty, technique, eliminations, placements: vec![] });
+                                    return Some(Hint { difficulty, technique, eliminations, placements: vec![], base_cells: vec![c1, c2, c3, c4], subtype, search_cost: inspected });
                                 }
                             }
                         }
@@ -223,19 +672,37 @@ fn detect_naked_subset(grid: &Grid, size: usize) -> Option<Hint> {
     None
 }
 
+/// Removes a naked subset's digits from the rest of every unit holding all of
+/// its cells. Subtype 1 marks a locked subset: the cells also share a box-line
+/// intersection, so two units are cleared at once.
+fn subset_eliminations(grid: &Grid, cells: &[usize], digits: Candidates) -> (Vec<(usize, u8)>, u8) {
+    let mut eliminations = Vec::new();
+    let mut shared_units = 0;
+    for unit in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()) {
+        if !cells.iter().all(|c| unit.contains(c)) { continue; }
+        shared_units += 1;
+        for &cell in unit.iter() {
+            if grid.values[cell] != 0 || cells.contains(&cell) { continue; }
+            for d in (grid.candidates[cell] & digits).iter() {
+                if !eliminations.contains(&(cell, d)) {
+                    eliminations.push((cell, d));
+                }
+            }
+        }
+    }
+    (eliminations, if shared_units > 1 { 1 } else { 0 })
+}
+
+#[allow(clippy::needless_range_loop)]
 fn detect_hidden_subset(grid: &Grid, size: usize) -> Option<Hint> {
-    let difficulty = match size {
-        2 => 18.0,
-        3 => 28.0,
-        4 => 42.0,
-        _ => 0.0,
-    };
     let technique = match size {
         2 => "hidden_pairs",
         3 => "hidden_triples",
         4 => "hidden_quads",
         _ => "",
     };
+    let difficulty = technique_difficulty(technique);
+    let mut inspected = 0;
 
     for unit in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()) {
         // Map digits to cells
@@ -246,7 +713,7 @@ fn detect_hidden_subset(grid: &Grid, size: usize) -> Option<Hint> {
             if grid.values[cell] == 0 {
                 let mask = grid.candidates[cell];
                 for d in 1..=9 {
-                    if (mask >> (d - 1)) & 1 == 1 {
+                    if mask.contains(d as u8) {
                         digit_cells[d] |= 1 << idx;
                         digit_counts[d] += 1;
                     }
@@ -273,6 +740,8 @@ fn detect_hidden_subset(grid: &Grid, size: usize) -> Option<Hint> {
                     let d2 = candidate_digits[j];
                     let union_cells = digit_cells[d1] | digit_cells[d2];
                     
+                    inspected += 1;
+                    
                     if union_cells.count_ones() as usize == 2 {
                          // Found hidden pair
                         let mut eliminations = Vec::new();
@@ -281,14 +750,14 @@ fn detect_hidden_subset(grid: &Grid, size: usize) -> Option<Hint> {
                                 let cell = unit[idx];
                                 let mask = grid.candidates[cell];
                                 for d in 1..=9 {
-                                    if d != d1 && d != d2 && (mask >> (d - 1)) & 1 == 1 {
+                                    if d != d1 && d != d2 && mask.contains(d as u8) {
                                         eliminations.push((cell, d as u8));
                                     }
                                 }
                             }
                         }
                         if !eliminations.is_empty() {
-                            return Some(Hint { difficulty, technique, eliminations, placements: vec![] });
+                            return Some(Hint { difficulty, technique, eliminations, placements: vec![], base_cells: (0..9).filter(|&idx| (union_cells >> idx) & 1 == 1).map(|idx| unit[idx]).collect(), subtype: 0, search_cost: inspected });
                         }
                     }
                 }
@@ -302,6 +771,8 @@ fn detect_hidden_subset(grid: &Grid, size: usize) -> Option<Hint> {
                         let d3 = candidate_digits[k];
                         let union_cells = digit_cells[d1] | digit_cells[d2] | digit_cells[d3];
                         
+                        inspected += 1;
+                        
                         if union_cells.count_ones() as usize == 3 {
                              // Found hidden triple
                             let mut eliminations = Vec::new();
@@ -310,14 +781,14 @@ fn detect_hidden_subset(grid: &Grid, size: usize) -> Option<Hint> {
                                     let cell = unit[idx];
                                     let mask = grid.candidates[cell];
                                     for d in 1..=9 {
-                                        if d != d1 && d != d2 && d != d3 && (mask >> (d - 1)) & 1 == 1 {
+                                        if d != d1 && d != d2 && d != d3 && mask.contains(d as u8) {
                                             eliminations.push((cell, d as u8));
                                         }
                                     }
                                 }
                             }
                             if !eliminations.is_empty() {
-                                return Some(Hint { difficulty, technique, eliminations, placements: vec![] });
+                                return Some(Hint { difficulty, technique, eliminations, placements: vec![], base_cells: (0..9).filter(|&idx| (union_cells >> idx) & 1 == 1).map(|idx| unit[idx]).collect(), subtype: 0, search_cost: inspected });
                             }
                         }
                     }
@@ -334,6 +805,8 @@ fn detect_hidden_subset(grid: &Grid, size: usize) -> Option<Hint> {
                             let d4 = candidate_digits[l];
                             let union_cells = digit_cells[d1] | digit_cells[d2] | digit_cells[d3] | digit_cells[d4];
                             
+                            inspected += 1;
+                            
                             if union_cells.count_ones() as usize == 4 {
                                  // Found hidden quad
                                 let mut eliminations = Vec::new();
@@ -342,14 +815,14 @@ fn detect_hidden_subset(grid: &Grid, size: usize) -> Option<Hint> {
                                         let cell = unit[idx];
                                         let mask = grid.candidates[cell];
                                         for d in 1..=9 {
-                                            if d != d1 && d != d2 && d != d3 && d != d4 && (mask >> (d - 1)) & 1 == 1 {
+                                            if d != d1 && d != d2 && d != d3 && d != d4 && mask.contains(d as u8) {
                                                 eliminations.push((cell, d as u8));
                                             }
                                         }
                                     }
                                 }
                                 if !eliminations.is_empty() {
-                                    return Some(Hint { difficulty, technique, eliminations, placements: vec![] });
+                                    return Some(Hint { difficulty, technique, eliminations, placements: vec![], base_cells: (0..9).filter(|&idx| (union_cells >> idx) & 1 == 1).map(|idx| unit[idx]).collect(), subtype: 0, search_cost: inspected });
                                 }
                             }
                         }
@@ -361,21 +834,64 @@ fn detect_hidden_subset(grid: &Grid, size: usize) -> Option<Hint> {
     None
 }
 
-fn detect_pointing_pairs(grid: &Grid) -> Option<Hint> {
+/// Pointing pair/triple: a digit confined to one line within a box. Three
+/// cells rate a little harder than two.
+fn detect_pointing_all(grid: &Grid) -> Vec<Hint> {
+    let mut hints = Vec::new();
+    for box_cells in BOXES.iter() {
+        for d in 1..=9 {
+            let cells: Vec<usize> = box_cells.iter().copied().filter(|&c| grid.values[c] == 0 && grid.candidates[c].contains(d)).collect();
+            if cells.len() < 2 || cells.len() > 3 { continue; }
+
+            let line = if cells.iter().all(|&c| c / 9 == cells[0] / 9) {
+                &ROWS[cells[0] / 9]
+            } else if cells.iter().all(|&c| c % 9 == cells[0] % 9) {
+                &COLS[cells[0] % 9]
+            } else {
+                continue;
+            };
+            let eliminations: Vec<(usize, u8)> = line
+                .iter()
+                .filter(|&&c| !cells.contains(&c) && grid.values[c] == 0 && grid.candidates[c].contains(d))
+                .map(|&c| (c, d))
+                .collect();
+            if !eliminations.is_empty() {
+                let technique = if cells.len() == 3 { "pointing_triples" } else { "pointing_pairs" };
+                hints.push(Hint {
+                    difficulty: technique_difficulty(technique),
+                    technique,
+                    eliminations,
+                    placements: vec![],
+                    base_cells: cells,
+                    subtype: 0,
+                    search_cost: 0,
+                });
+            }
+        }
+    }
+    hints
+}
+
+#[allow(clippy::needless_range_loop)]
+fn detect_pointing_pairs(grid: &Grid, wanted: TechniqueSet) -> Option<Hint> {
     // Box-Line interaction
+    let mut inspected = 0;
     for box_idx in 0..9 {
         let box_cells = BOXES[box_idx];
         for d in 1..=9 {
             let mut candidates_in_box = [0usize; 9];
             let mut count = 0;
             for &cell in &box_cells {
-                if grid.values[cell] == 0 && (grid.candidates[cell] >> (d - 1)) & 1 == 1 {
+                if grid.values[cell] == 0 && grid.candidates[cell].contains(d) {
                     candidates_in_box[count] = cell;
                     count += 1;
                 }
             }
             
-            if count >= 2 && count <= 3 {
+            if (2..=3).contains(&count) {
+                inspected += 1;
+                let technique = if count == 3 { "pointing_triples" } else { "pointing_pairs" };
+                if !wanted.allows(technique) { continue; }
                 // Check Row
                 let row0 = candidates_in_box[0] / 9;
                 let mut all_same_row = true;
@@ -399,16 +915,19 @@ fn detect_pointing_pairs(grid: &Grid) -> Option<Hint> {
                             }
                         }
                         
-                        if !is_candidate && grid.values[cell] == 0 && (grid.candidates[cell] >> (d - 1)) & 1 == 1 {
-                            eliminations.push((cell, d as u8));
+                        if !is_candidate && grid.values[cell] == 0 && grid.candidates[cell].contains(d) {
+                            eliminations.push((cell, d));
                         }
                     }
                     if !eliminations.is_empty() {
                         return Some(Hint {
-                            difficulty: 12.0,
-                            technique: "pointing_pairs",
+                            difficulty: technique_difficulty(technique),
+                            technique,
                             eliminations,
                             placements: vec![],
+                            base_cells: candidates_in_box[..count].to_vec(),
+                            subtype: 0,
+                            search_cost: inspected,
                         });
                     }
                 }
@@ -436,16 +955,19 @@ fn detect_pointing_pairs(grid: &Grid) -> Option<Hint> {
                             }
                         }
                         
-                        if !is_candidate && grid.values[cell] == 0 && (grid.candidates[cell] >> (d - 1)) & 1 == 1 {
-                            eliminations.push((cell, d as u8));
+                        if !is_candidate && grid.values[cell] == 0 && grid.candidates[cell].contains(d) {
+                            eliminations.push((cell, d));
                         }
                     }
                     if !eliminations.is_empty() {
                         return Some(Hint {
-                            difficulty: 12.0,
-                            technique: "pointing_pairs",
+                            difficulty: technique_difficulty(technique),
+                            technique,
                             eliminations,
                             placements: vec![],
+                            base_cells: candidates_in_box[..count].to_vec(),
+                            subtype: 0,
+                            search_cost: inspected,
                         });
                     }
                 }
@@ -455,26 +977,32 @@ fn detect_pointing_pairs(grid: &Grid) -> Option<Hint> {
     None
 }
 
-fn detect_box_line_reduction(grid: &Grid) -> Option<Hint> {
+/// Box-line reduction: a digit confined to one box within a line, rated
+/// by the number of cells like pointing.
+#[allow(clippy::needless_range_loop)]
+fn detect_box_line_reduction(grid: &Grid, wanted: TechniqueSet) -> Option<Hint> {
     // Line-Box interaction
+    let mut inspected = 0;
     for d in 1..=9 {
         // Rows
         for r in 0..9 {
             let mut candidates_in_row = [0usize; 9];
             let mut count = 0;
             for &cell in &ROWS[r] {
-                if grid.values[cell] == 0 && (grid.candidates[cell] >> (d - 1)) & 1 == 1 {
+                if grid.values[cell] == 0 && grid.candidates[cell].contains(d) {
                     candidates_in_row[count] = cell;
                     count += 1;
                 }
             }
             
-            if count >= 2 && count <= 3 {
-                let box0 = (candidates_in_row[0] / 9 / 3) * 3 + (candidates_in_row[0] % 9 / 3);
+            if (2..=3).contains(&count) {
+                inspected += 1;
+                let technique = if count == 3 { "box_line_triples" } else { "box_line_reduction" };
+                if !wanted.allows(technique) { continue; }
+                let box0 = box_of(candidates_in_row[0]);
                 let mut all_same_box = true;
                 for i in 1..count {
-                    let c = candidates_in_row[i];
-                    if (c / 9 / 3) * 3 + (c % 9 / 3) != box0 {
+                    if box_of(candidates_in_row[i]) != box0 {
                         all_same_box = false;
                         break;
                     }
@@ -493,16 +1021,19 @@ fn detect_box_line_reduction(grid: &Grid) -> Option<Hint> {
                             }
                         }
                         
-                        if !is_candidate && grid.values[cell] == 0 && (grid.candidates[cell] >> (d - 1)) & 1 == 1 {
-                            eliminations.push((cell, d as u8));
+                        if !is_candidate && grid.values[cell] == 0 && grid.candidates[cell].contains(d) {
+                            eliminations.push((cell, d));
                         }
                     }
                     if !eliminations.is_empty() {
                         return Some(Hint {
-                            difficulty: 14.0,
-                            technique: "box_line_reduction",
+                            difficulty: technique_difficulty(technique),
+                            technique,
                             eliminations,
                             placements: vec![],
+                            base_cells: candidates_in_row[..count].to_vec(),
+                            subtype: 0,
+                            search_cost: inspected,
                         });
                     }
                 }
@@ -513,18 +1044,20 @@ fn detect_box_line_reduction(grid: &Grid) -> Option<Hint> {
             let mut candidates_in_col = [0usize; 9];
             let mut count = 0;
             for &cell in &COLS[c] {
-                if grid.values[cell] == 0 && (grid.candidates[cell] >> (d - 1)) & 1 == 1 {
+                if grid.values[cell] == 0 && grid.candidates[cell].contains(d) {
                     candidates_in_col[count] = cell;
                     count += 1;
                 }
             }
             
-            if count >= 2 && count <= 3 {
-                let box0 = (candidates_in_col[0] / 9 / 3) * 3 + (candidates_in_col[0] % 9 / 3);
+            if (2..=3).contains(&count) {
+                inspected += 1;
+                let technique = if count == 3 { "box_line_triples" } else { "box_line_reduction" };
+                if !wanted.allows(technique) { continue; }
+                let box0 = box_of(candidates_in_col[0]);
                 let mut all_same_box = true;
                 for i in 1..count {
-                    let c = candidates_in_col[i];
-                    if (c / 9 / 3) * 3 + (c % 9 / 3) != box0 {
+                    if box_of(candidates_in_col[i]) != box0 {
                         all_same_box = false;
                         break;
                     }
@@ -543,16 +1076,19 @@ fn detect_box_line_reduction(grid: &Grid) -> Option<Hint> {
                             }
                         }
                         
-                        if !is_candidate && grid.values[cell] == 0 && (grid.candidates[cell] >> (d - 1)) & 1 == 1 {
-                            eliminations.push((cell, d as u8));
+                        if !is_candidate && grid.values[cell] == 0 && grid.candidates[cell].contains(d) {
+                            eliminations.push((cell, d));
                         }
                     }
                     if !eliminations.is_empty() {
                         return Some(Hint {
-                            difficulty: 14.0,
-                            technique: "box_line_reduction",
+                            difficulty: technique_difficulty(technique),
+                            technique,
                             eliminations,
                             placements: vec![],
+                            base_cells: candidates_in_col[..count].to_vec(),
+                            subtype: 0,
+                            search_cost: inspected,
                         });
                     }
                 }
@@ -562,34 +1098,51 @@ fn detect_box_line_reduction(grid: &Grid) -> Option<Hint> {
     None
 }
 
+/// The two cells of `unit` holding `digit`, if there are exactly two.
+fn unit_conjugate(grid: &Grid, unit: &[usize; 9], digit: u8) -> Option<(usize, usize)> {
+    let mut found = [0usize; 2];
+    let mut count = 0;
+    for &cell in unit {
+        if grid.values[cell] == 0 && grid.candidates[cell].contains(digit) {
+            if count == 2 { return None; }
+            found[count] = cell;
+            count += 1;
+        }
+    }
+    if count == 2 { Some((found[0], found[1])) } else { None }
+}
+
+/// Conjugate pairs (strong links) for `digit`: the two cells that are its only
+/// places in some row, column or box. Each pair is listed once, scanning rows,
+/// then columns, then boxes.
+pub fn conjugate_pairs(grid: &Grid, digit: u8) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for unit in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()) {
+        if let Some(pair) = unit_conjugate(grid, unit, digit) {
+            if !pairs.contains(&pair) {
+                pairs.push(pair);
+            }
+        }
+    }
+    pairs
+}
+
+#[allow(clippy::needless_range_loop)]
 fn detect_x_wing(grid: &Grid) -> Option<Hint> {
     for d in 1..=9 {
         // Rows
-        let mut rows_with_2 = [0usize; 9]; // Stores row index
         let mut row_cells = [[0usize; 2]; 9]; // Stores the 2 cell indices for each row
         let mut count = 0;
         
         for r in 0..9 {
-            let mut cells = [0usize; 9];
-            let mut c_count = 0;
-            for &cell in &ROWS[r] {
-                if grid.values[cell] == 0 && (grid.candidates[cell] >> (d - 1)) & 1 == 1 {
-                    cells[c_count] = cell;
-                    c_count += 1;
-                }
-            }
-            if c_count == 2 {
-                rows_with_2[count] = r;
-                row_cells[count][0] = cells[0];
-                row_cells[count][1] = cells[1];
+            if let Some((a, b)) = unit_conjugate(grid, &ROWS[r], d) {
+                row_cells[count] = [a, b];
                 count += 1;
             }
         }
         
         for i in 0..count {
             for j in i+1..count {
-                let r1 = rows_with_2[i];
-                let r2 = rows_with_2[j];
                 let cells1 = row_cells[i];
                 let cells2 = row_cells[j];
                 
@@ -603,17 +1156,20 @@ fn detect_x_wing(grid: &Grid) -> Option<Hint> {
                     let mut eliminations = Vec::new();
                     for &c in &[c1a, c1b] {
                         for &cell in &COLS[c] {
-                            if cell != cells1[0] && cell != cells1[1] && cell != cells2[0] && cell != cells2[1] && grid.values[cell] == 0 && (grid.candidates[cell] >> (d - 1)) & 1 == 1 {
-                                eliminations.push((cell, d as u8));
+                            if cell != cells1[0] && cell != cells1[1] && cell != cells2[0] && cell != cells2[1] && grid.values[cell] == 0 && grid.candidates[cell].contains(d) {
+                                eliminations.push((cell, d));
                             }
                         }
                     }
                     if !eliminations.is_empty() {
                         return Some(Hint {
-                            difficulty: 46.0,
+                            difficulty: technique_difficulty("x_wing"),
                             technique: "x_wing",
                             eliminations,
                             placements: vec![],
+                            base_cells: vec![cells1[0], cells1[1], cells2[0], cells2[1]],
+                            subtype: 0,
+                            search_cost: 0,
                         });
                     }
                 }
@@ -621,31 +1177,18 @@ fn detect_x_wing(grid: &Grid) -> Option<Hint> {
         }
         
         // Cols
-        let mut cols_with_2 = [0usize; 9];
         let mut col_cells = [[0usize; 2]; 9];
         let mut count = 0;
         
         for c in 0..9 {
-            let mut cells = [0usize; 9];
-            let mut c_count = 0;
-            for &cell in &COLS[c] {
-                if grid.values[cell] == 0 && (grid.candidates[cell] >> (d - 1)) & 1 == 1 {
-                    cells[c_count] = cell;
-                    c_count += 1;
-                }
-            }
-            if c_count == 2 {
-                cols_with_2[count] = c;
-                col_cells[count][0] = cells[0];
-                col_cells[count][1] = cells[1];
+            if let Some((a, b)) = unit_conjugate(grid, &COLS[c], d) {
+                col_cells[count] = [a, b];
                 count += 1;
             }
         }
         
         for i in 0..count {
             for j in i+1..count {
-                let c1 = cols_with_2[i];
-                let c2 = cols_with_2[j];
                 let cells1 = col_cells[i];
                 let cells2 = col_cells[j];
                 
@@ -659,17 +1202,106 @@ fn detect_x_wing(grid: &Grid) -> Option<Hint> {
                     let mut eliminations = Vec::new();
                     for &r in &[r1a, r1b] {
                         for &cell in &ROWS[r] {
-                            if cell != cells1[0] && cell != cells1[1] && cell != cells2[0] && cell != cells2[1] && grid.values[cell] == 0 && (grid.candidates[cell] >> (d - 1)) & 1 == 1 {
-                                eliminations.push((cell, d as u8));
+                            if cell != cells1[0] && cell != cells1[1] && cell != cells2[0] && cell != cells2[1] && grid.values[cell] == 0 && grid.candidates[cell].contains(d) {
+                                eliminations.push((cell, d));
                             }
                         }
                     }
                     if !eliminations.is_empty() {
                         return Some(Hint {
-                            difficulty: 46.0,
+                            difficulty: technique_difficulty("x_wing"),
                             technique: "x_wing",
                             eliminations,
                             placements: vec![],
+                            base_cells: vec![cells1[0], cells1[1], cells2[0], cells2[1]],
+                            subtype: 0,
+                            search_cost: 0,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Turbot Fish family: single-digit chains strong-weak-strong, A=B-C=D, so A or
+/// D holds the digit and cells seeing both lose it. Named by shape: a
+/// Skyscraper has two parallel line links, a 2-String Kite a row and a column
+/// link meeting in a box. An Empty Rectangle uses a box whose candidates fit in
+/// one row plus one column as a grouped strong link. All links are built once
+/// per digit.
+fn detect_turbot_fish(grid: &Grid, wanted: TechniqueSet) -> Option<Hint> {
+    let has = |cell: usize, d: u8| grid.values[cell] == 0 && grid.candidates[cell].contains(d);
+
+    for d in 1..=9u8 {
+        let pairs = conjugate_pairs(grid, d);
+        // Both directions of every strong link
+        let links: Vec<(usize, usize)> = pairs.iter().flat_map(|&(a, b)| [(a, b), (b, a)]).collect();
+        let is_row = |(a, b): (usize, usize)| a / 9 == b / 9;
+        let is_col = |(a, b): (usize, usize)| a % 9 == b % 9;
+
+        for (i, &(a, b)) in links.iter().enumerate() {
+            for &(c, e) in &links[i + 1..] {
+                if [a, b].contains(&c) || [a, b].contains(&e) || !can_see(b, c) { continue; }
+
+                let eliminations: Vec<(usize, u8)> = (0..SIZE)
+                    .filter(|&cell| ![a, b, c, e].contains(&cell) && has(cell, d) && can_see(cell, a) && can_see(cell, e))
+                    .map(|cell| (cell, d))
+                    .collect();
+                if eliminations.is_empty() { continue; }
+
+                let same_box = box_of(b) == box_of(c);
+                let technique = if (is_row((a, b)) && is_row((c, e)) && b % 9 == c % 9)
+                    || (is_col((a, b)) && is_col((c, e)) && b / 9 == c / 9) {
+                    "skyscraper"
+                } else if ((is_row((a, b)) && is_col((c, e))) || (is_col((a, b)) && is_row((c, e)))) && same_box {
+                    "two_string_kite"
+                } else {
+                    "turbot_fish"
+                };
+                if !wanted.allows(technique) { continue; }
+                return Some(Hint {
+                    difficulty: technique_difficulty(technique),
+                    technique,
+                    eliminations,
+                    placements: vec![],
+                    base_cells: vec![a, b, c, e],
+                    subtype: 0,
+                    search_cost: 0,
+                });
+            }
+        }
+
+        if !wanted.contains(TechniqueSet::EMPTY_RECTANGLE) { continue; }
+        for (bx, unit) in BOXES.iter().enumerate() {
+            let in_box: Vec<usize> = unit.iter().copied().filter(|&cell| has(cell, d)).collect();
+            if in_box.len() < 2 { continue; }
+            let (band, stack) = (bx / 3, bx % 3);
+
+            for r in band * 3..band * 3 + 3 {
+                for col in stack * 3..stack * 3 + 3 {
+                    if !in_box.iter().all(|&cell| cell / 9 == r || cell % 9 == col) { continue; }
+
+                    // p on the hinge row (or column) outside the box, strongly linked to q
+                    for &(p, q) in &links {
+                        let target = if is_col((p, q)) && p / 9 == r && p % 9 / 3 != stack && q / 9 / 3 != band {
+                            q / 9 * 9 + col
+                        } else if is_row((p, q)) && p % 9 == col && p / 27 != band && q % 9 / 3 != stack {
+                            r * 9 + q % 9
+                        } else {
+                            continue;
+                        };
+                        if !has(target, d) { continue; }
+
+                        return Some(Hint {
+                            difficulty: technique_difficulty("empty_rectangle"),
+                            technique: "empty_rectangle",
+                            eliminations: vec![(target, d)],
+                            placements: vec![],
+                            base_cells: in_box.iter().copied().chain([p, q]).collect(),
+                            subtype: 0,
+                            search_cost: 0,
                         });
                     }
                 }
@@ -679,27 +1311,83 @@ fn detect_x_wing(grid: &Grid) -> Option<Hint> {
     None
 }
 
+/// Finned X-Wing: an X-Wing whose second base line also holds the digit in
+/// "fin" cells, all in the box of one corner. Either the fish or a fin is true,
+/// so cells in that corner's cover line and box lose the digit. When the corner
+/// itself lacks the digit the fish is a Sashimi X-Wing.
+fn detect_finned_x_wing(grid: &Grid, wanted: TechniqueSet) -> Option<Hint> {
+    for d in 1..=9u8 {
+        for by_row in [true, false] {
+            // Lines are rows (positions = columns) or columns (positions = rows)
+            let cell_at = |line: usize, pos: usize| if by_row { line * 9 + pos } else { pos * 9 + line };
+            let has = |line: usize, pos: usize| {
+                let cell = cell_at(line, pos);
+                grid.values[cell] == 0 && grid.candidates[cell].contains(d)
+            };
+
+            for base in 0..9 {
+                let positions: Vec<usize> = (0..9).filter(|&p| has(base, p)).collect();
+                if positions.len() != 2 { continue; }
+
+                for other in 0..9 {
+                    if other == base { continue; }
+                    let other_positions: Vec<usize> = (0..9).filter(|&p| has(other, p)).collect();
+
+                    for (anchor, corner) in [(positions[0], positions[1]), (positions[1], positions[0])] {
+                        if !other_positions.contains(&anchor) { continue; }
+                        let fins: Vec<usize> = other_positions.iter().copied().filter(|&p| p != anchor && p != corner).collect();
+                        if fins.is_empty() || fins.iter().any(|&p| p / 3 != corner / 3) { continue; }
+
+                        let mut eliminations = Vec::new();
+                        for line in (other / 3) * 3..(other / 3) * 3 + 3 {
+                            if line != other && line != base && has(line, corner) {
+                                eliminations.push((cell_at(line, corner), d));
+                            }
+                        }
+                        let technique = if other_positions.contains(&corner) { "finned_x_wing" } else { "sashimi_x_wing" };
+                        if !eliminations.is_empty() && wanted.allows(technique) {
+                            return Some(Hint {
+                                difficulty: technique_difficulty(technique),
+                                technique,
+                                eliminations,
+                                placements: vec![],
+                                base_cells: positions.iter().map(|&p| cell_at(base, p)).chain(other_positions.iter().map(|&p| cell_at(other, p))).collect(),
+                                subtype: 0,
+                                search_cost: 0,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
 fn detect_y_wing(grid: &Grid) -> Option<Hint> {
     let mut bivalue_cells = Vec::new();
     for i in 0..SIZE {
-        if grid.values[i] == 0 && grid.candidates[i].count_ones() == 2 {
+        if grid.values[i] == 0 && grid.candidates[i].len() == 2 {
             bivalue_cells.push(i);
         }
     }
     
     if bivalue_cells.len() < 3 { return None; }
     
+    let mut inspected = 0;
     for i in 0..bivalue_cells.len() {
         for j in i+1..bivalue_cells.len() {
             for k in j+1..bivalue_cells.len() {
                 let c1 = bivalue_cells[i];
                 let c2 = bivalue_cells[j];
                 let c3 = bivalue_cells[k];
+                inspected += 1;
                 
                 // Try each as pivot
-                if let Some(h) = check_y_wing(grid, c1, c2, c3) { return Some(h); }
-                if let Some(h) = check_y_wing(grid, c2, c1, c3) { return Some(h); }
-                if let Some(h) = check_y_wing(grid, c3, c1, c2) { return Some(h); }
+                let found = check_y_wing(grid, c1, c2, c3)
+                    .or_else(|| check_y_wing(grid, c2, c1, c3))
+                    .or_else(|| check_y_wing(grid, c3, c1, c2));
+                if let Some(h) = found { return Some(Hint { search_cost: inspected, ..h }); }
             }
         }
     }
@@ -707,6 +1395,36 @@ fn detect_y_wing(grid: &Grid) -> Option<Hint> {
 }
 
 fn check_y_wing(grid: &Grid, pivot: usize, p1: usize, p2: usize) -> Option<Hint> {
+    let digit_c = y_wing_digit(grid, pivot, p1, p2)?;
+    
+    // Elimination: Cells seeing both P1 and P2 containing C
+    let mut eliminations = Vec::new();
+    let peers1 = get_peers(p1);
+    let peers2 = get_peers(p2);
+    
+    for &peer in &peers1 {
+        if peers2.contains(&peer) && grid.values[peer] == 0 && grid.candidates[peer].contains(digit_c) {
+            eliminations.push((peer, digit_c));
+        }
+    }
+    
+    if !eliminations.is_empty() {
+        return Some(Hint {
+            difficulty: technique_difficulty("y_wing"),
+            technique: "y_wing",
+            eliminations,
+            placements: vec![],
+            base_cells: vec![pivot, p1, p2],
+            subtype: 0,
+            search_cost: 0,
+        });
+    }
+    
+    None
+}
+
+/// The digit C if bivalue `pivot` (AB) sees `p1` (AC) and `p2` (BC).
+fn y_wing_digit(grid: &Grid, pivot: usize, p1: usize, p2: usize) -> Option<u8> {
     if !can_see(pivot, p1) || !can_see(pivot, p2) { return None; }
     
     let cand_pivot = grid.candidates[pivot];
@@ -715,16 +1433,16 @@ fn check_y_wing(grid: &Grid, pivot: usize, p1: usize, p2: usize) -> Option<Hint>
     
     // Union of all candidates must have exactly 3 bits set
     let all_cands = cand_pivot | cand_p1 | cand_p2;
-    if all_cands.count_ones() != 3 { return None; }
+    if all_cands.len() != 3 { return None; }
     
     // Check structure: Pivot(AB), P1(AC), P2(BC)
     // Common between Pivot and P1: A
     let common_p1 = cand_pivot & cand_p1;
-    if common_p1.count_ones() != 1 { return None; }
+    if common_p1.len() != 1 { return None; }
     
     // Common between Pivot and P2: B
     let common_p2 = cand_pivot & cand_p2;
-    if common_p2.count_ones() != 1 { return None; }
+    if common_p2.len() != 1 { return None; }
     
     let a = common_p1;
     let b = common_p2;
@@ -735,48 +1453,187 @@ fn check_y_wing(grid: &Grid, pivot: usize, p1: usize, p2: usize) -> Option<Hint>
     let c_p1 = cand_p1 & !a;
     let c_p2 = cand_p2 & !b;
     
-    if c_p1.count_ones() != 1 || c_p2.count_ones() != 1 { return None; }
+    if c_p1.len() != 1 || c_p2.len() != 1 { return None; }
     let c = c_p1;
     if c != c_p2 { return None; }
     
-    // c is a bitmask (1 << (digit-1))
-    let digit_c = c.trailing_zeros() as u8 + 1;
-    
-    // Elimination: Cells seeing both P1 and P2 containing C
-    let mut eliminations = Vec::new();
-    let peers1 = get_peers(p1);
-    let peers2 = get_peers(p2);
-    
-    for &peer in &peers1 {
-        if peers2.contains(&peer) && grid.values[peer] == 0 && (grid.candidates[peer] & c) != 0 {
-            eliminations.push((peer, digit_c));
-        }
-    }
-    
-    if !eliminations.is_empty() {
-        return Some(Hint {
-            difficulty: 50.0,
-            technique: "y_wing",
-            eliminations,
-            placements: vec![],
-        });
+    c.first()
+}
+
+fn can_see(s1: usize, s2: usize) -> bool {
+    row_of(s1) == row_of(s2) || col_of(s1) == col_of(s2) || box_of(s1) == box_of(s2)
+}
+
+fn group_sees(group: &[usize], cell: usize) -> bool {
+    group.iter().all(|&g| g != cell && can_see(g, cell))
+}
+
+/// Y-Wing / XYZ-Wing where one pincer is a group: a 2-3 cell ALS inside a
+/// box-line intersection. If the pivot takes the group's linking digit B, every
+/// B cell of the group sees it, so the rest of the group locks and must hold C.
+fn detect_grouped_wing(grid: &Grid) -> Option<Hint> {
+    let groups: Vec<Als> = find_als(grid, 3)
+        .into_iter()
+        .filter(|a| {
+            a.cells.len() >= 2
+                && a.cells.iter().all(|&c| box_of(c) == box_of(a.cells[0]))
+                && (a.cells.iter().all(|&c| c / 9 == a.cells[0] / 9) || a.cells.iter().all(|&c| c % 9 == a.cells[0] % 9))
+        })
+        .collect();
+    if groups.is_empty() { return None; }
+
+    for pivot in 0..SIZE {
+        if grid.values[pivot] != 0 { continue; }
+        let pivot_mask = grid.candidates[pivot];
+        let xyz = match pivot_mask.len() {
+            2 => false,
+            3 => true,
+            _ => continue,
+        };
+
+        for pincer in 0..SIZE {
+            if pincer == pivot || grid.values[pincer] != 0 || !can_see(pivot, pincer) { continue; }
+            let pincer_mask = grid.candidates[pincer];
+            if pincer_mask.len() != 2 { continue; }
+
+            let shared = pivot_mask & pincer_mask;
+            if shared.len() != if xyz { 2 } else { 1 } { continue; }
+            let b = (pivot_mask & !shared).first().unwrap();
+            // XY: pivot {A,B}, pincer {A,C}. XYZ: pivot {A,B,C}, pincer {A,C} with either as A.
+            let options: Vec<u8> = if xyz {
+                let lo = shared.first().unwrap();
+                let hi = (shared & !Candidates::from_digit(lo)).first().unwrap();
+                vec![hi, lo]
+            } else {
+                vec![(pincer_mask & !pivot_mask).first().unwrap()]
+            };
+
+            for c in options {
+                for group in &groups {
+                    if (group.cell_bits >> pivot) & 1 == 1 || (group.cell_bits >> pincer) & 1 == 1 { continue; }
+                    if !group.mask.contains(b) || !group.mask.contains(c) { continue; }
+                    if !group.cells.iter().all(|&g| !grid.candidates[g].contains(b) || can_see(g, pivot)) { continue; }
+
+                    let c_cells: Vec<usize> = group.cells.iter().copied().filter(|&g| grid.candidates[g].contains(c)).collect();
+                    let mut eliminations = Vec::new();
+                    for cell in 0..SIZE {
+                        if cell == pivot || cell == pincer || grid.values[cell] != 0 || !grid.candidates[cell].contains(c) { continue; }
+                        if (group.cell_bits >> cell) & 1 == 1 { continue; }
+                        if !can_see(cell, pincer) || !group_sees(&c_cells, cell) { continue; }
+                        if xyz && !can_see(cell, pivot) { continue; }
+                        eliminations.push((cell, c));
+                    }
+
+                    if !eliminations.is_empty() {
+                        return Some(Hint {
+                            difficulty: technique_difficulty("grouped_wing"),
+                            technique: "grouped_wing",
+                            eliminations,
+                            placements: vec![],
+                            base_cells: [pivot, pincer].iter().chain(&group.cells).copied().collect(),
+                            subtype: 0,
+                            search_cost: 0,
+                        });
+                    }
+                }
+            }
+        }
     }
-    
     None
 }
 
-fn can_see(s1: usize, s2: usize) -> bool {
-    let r1 = s1 / 9;
-    let c1 = s1 % 9;
-    let b1 = (r1 / 3) * 3 + (c1 / 3);
-    
-    let r2 = s2 / 9;
-    let c2 = s2 % 9;
-    let b2 = (r2 / 3) * 3 + (c2 / 3);
-    
-    r1 == r2 || c1 == c2 || b1 == b2
+/// Strong links a transported wing may follow from each pincer.
+const TRANSPORT_MAX_LINKS: usize = 4;
+
+/// Transported Wing: a Y-Wing (subtype 0) or W-Wing (subtype 1) proves one of
+/// two pincers holds C. If a pincer is C, a peer is not, so that peer's
+/// conjugate partner on C is; following up to `max_len` such weak-strong steps
+/// moves each end to more cells that must be C with it. A cell seeing an end
+/// on each side loses C. Only eliminations the plain wing misses are reported.
+fn detect_transported_wing(grid: &Grid, max_len: usize) -> Option<Hint> {
+    let bivalue: Vec<usize> = (0..SIZE).filter(|&i| grid.values[i] == 0 && grid.candidates[i].len() == 2).collect();
+    let mut wings: Vec<(Vec<usize>, usize, usize, u8, u8)> = Vec::new(); // (cells, pincer, pincer, C, subtype)
+
+    for &pivot in &bivalue {
+        for (i, &p1) in bivalue.iter().enumerate() {
+            for &p2 in &bivalue[i + 1..] {
+                if pivot == p1 || pivot == p2 { continue; }
+                if let Some(c) = y_wing_digit(grid, pivot, p1, p2) {
+                    wings.push((vec![pivot, p1, p2], p1, p2, c, 0));
+                }
+            }
+        }
+    }
+
+    // W-Wing: twin bivalue cells AB, joined by a strong link on A between
+    // their peers, so one of them is B
+    for (i, &w1) in bivalue.iter().enumerate() {
+        for &w2 in &bivalue[i + 1..] {
+            if grid.candidates[w1] != grid.candidates[w2] || can_see(w1, w2) { continue; }
+            for a in grid.candidates[w1].iter() {
+                let b = (grid.candidates[w1] & !Candidates::from_digit(a)).first().unwrap();
+                for (x, y) in conjugate_pairs(grid, a) {
+                    for (x, y) in [(x, y), (y, x)] {
+                        if x != w1 && y != w2 && can_see(x, w1) && can_see(y, w2) && x != w2 && y != w1 {
+                            wings.push((vec![w1, w2, x, y], w1, w2, b, 1));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    for (cells, p1, p2, c, subtype) in wings {
+        let strong = conjugate_pairs(grid, c);
+        let ends1 = transport(&strong, p1, max_len);
+        let ends2 = transport(&strong, p2, max_len);
+        if ends1.len() == 1 && ends2.len() == 1 { continue; }
+
+        let eliminations: Vec<(usize, u8)> = (0..SIZE)
+            .filter(|&e| grid.values[e] == 0 && grid.candidates[e].contains(c))
+            .filter(|&e| !(can_see(e, p1) && can_see(e, p2)))
+            .filter(|&e| ends1.iter().any(|&q| q != e && can_see(q, e)) && ends2.iter().any(|&q| q != e && can_see(q, e)))
+            .map(|e| (e, c))
+            .collect();
+        if !eliminations.is_empty() {
+            return Some(Hint {
+                difficulty: technique_difficulty("transported_wing"),
+                technique: "transported_wing",
+                eliminations,
+                placements: vec![],
+                base_cells: cells,
+                subtype,
+                search_cost: 0,
+            });
+        }
+    }
+    None
 }
 
+/// Cells that hold the digit whenever `start` does, via up to `max_len`
+/// peer-then-conjugate steps over `strong`. Includes `start`.
+fn transport(strong: &[(usize, usize)], start: usize, max_len: usize) -> Vec<usize> {
+    let mut ends = vec![start];
+    let mut frontier = vec![start];
+    for _ in 0..max_len {
+        let mut next = Vec::new();
+        for &u in &frontier {
+            for &(a, b) in strong {
+                for (z, y) in [(a, b), (b, a)] {
+                    if z != u && can_see(u, z) && !ends.contains(&y) {
+                        ends.push(y);
+                        next.push(y);
+                    }
+                }
+            }
+        }
+        if next.is_empty() { break; }
+        frontier = next;
+    }
+    ends
+}
+
+#[allow(clippy::needless_range_loop)]
 fn detect_simple_coloring(grid: &Grid) -> Option<Hint> {
     // Simplified implementation of Simple Coloring
     // Only checking Rule 2 (Conflict) and Rule 4 (Witness)
@@ -788,69 +1645,20 @@ fn detect_simple_coloring(grid: &Grid) -> Option<Hint> {
     // 23KB is fine for stack (usually 1MB+).
     // Let's use a single flat array: neighbors[d * 324 + cell * 4 + i]
     // 9 * 324 = 2916 usize elements. 2916 * 4 bytes (wasm32) = ~11KB. Safe.
-    
-    // Bitwise Counting Optimization
-    // We scan each unit once to find digits that appear exactly twice
-    
     let mut neighbor_counts = [0usize; 729]; // 9 * 81
     let mut neighbors = [0usize; 2916]; // 9 * 81 * 4
     let mut has_links = [false; 9];
     
-    for unit in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()) {
-        let mut seen_once = 0u16;
-        let mut seen_twice = 0u16;
-        let mut seen_more = 0u16;
-        
-        // Pass 1: Count occurrences using bitmasks
-        for &cell in unit.iter() {
-            if grid.values[cell] == 0 {
-                let c = grid.candidates[cell];
-                seen_more |= seen_twice & c;
-                seen_twice |= seen_once & c;
-                seen_once |= c;
-            }
-        }
-        
-        let exactly_twice = seen_twice & !seen_more;
-        
-        if exactly_twice == 0 { continue; }
-        
-        // Pass 2: Find the cells for the valid digits
-        // We only care about digits in exactly_twice
-        
-        let mut found_first = 0u16;
-        let mut firsts = [0usize; 9];
-        
-        for &cell in unit.iter() {
-            if grid.values[cell] == 0 {
-                let c = grid.candidates[cell] & exactly_twice;
-                if c == 0 { continue; }
-                
-                for d in 0..9 {
-                    if (c >> d) & 1 == 1 {
-                        if (found_first >> d) & 1 == 0 {
-                            firsts[d] = cell;
-                            found_first |= 1 << d;
-                        } else {
-                            // Found second one, add edge
-                            let c1 = firsts[d];
-                            let c2 = cell;
-                            let base_idx = d * 81;
-                            let base_neighbor_idx = d * 324;
-                            
-                            if neighbor_counts[base_idx + c1] < 4 {
-                                neighbors[base_neighbor_idx + c1 * 4 + neighbor_counts[base_idx + c1]] = c2;
-                                neighbor_counts[base_idx + c1] += 1;
-                            }
-                            if neighbor_counts[base_idx + c2] < 4 {
-                                neighbors[base_neighbor_idx + c2 * 4 + neighbor_counts[base_idx + c2]] = c1;
-                                neighbor_counts[base_idx + c2] += 1;
-                            }
-                            has_links[d] = true;
-                        }
-                    }
-                }
-            }
+    for d in 0..9 {
+        let base_idx = d * 81;
+        let base_neighbor_idx = d * 324;
+        for (c1, c2) in conjugate_pairs(grid, d as u8 + 1) {
+            // A cell has at most three units, so at most three distinct links
+            neighbors[base_neighbor_idx + c1 * 4 + neighbor_counts[base_idx + c1]] = c2;
+            neighbor_counts[base_idx + c1] += 1;
+            neighbors[base_neighbor_idx + c2 * 4 + neighbor_counts[base_idx + c2]] = c1;
+            neighbor_counts[base_idx + c2] += 1;
+            has_links[d] = true;
         }
     }
     
@@ -922,10 +1730,13 @@ fn detect_simple_coloring(grid: &Grid) -> Option<Hint> {
                     }
                     if !eliminations.is_empty() {
                         return Some(Hint {
-                            difficulty: 54.0,
+                            difficulty: technique_difficulty("simple_coloring"),
                             technique: "simple_coloring",
                             eliminations,
                             placements: vec![],
+                            base_cells: color_a_nodes[..color_a_count].iter().chain(&color_b_nodes[..color_b_count]).copied().collect(),
+                            subtype: 0,
+                            search_cost: 0,
                         });
                     }
                 }
@@ -933,7 +1744,7 @@ fn detect_simple_coloring(grid: &Grid) -> Option<Hint> {
                 // Rule 4
                 let mut eliminations = Vec::new();
                 for i in 0..SIZE {
-                    if grid.values[i] == 0 && (grid.candidates[i] >> d_idx) & 1 == 1 && colors[i] == 0 {
+                    if grid.values[i] == 0 && grid.candidates[i].contains(d) && colors[i] == 0 {
                         let mut sees_a = false;
                         let mut sees_b = false;
                         for &peer in &get_peers(i) {
@@ -948,10 +1759,13 @@ fn detect_simple_coloring(grid: &Grid) -> Option<Hint> {
                 
                 if !eliminations.is_empty() {
                     return Some(Hint {
-                        difficulty: 54.0,
+                        difficulty: technique_difficulty("simple_coloring"),
                         technique: "simple_coloring",
                         eliminations,
                         placements: vec![],
+                        base_cells: color_a_nodes[..color_a_count].iter().chain(&color_b_nodes[..color_b_count]).copied().collect(),
+                        subtype: 0,
+                        search_cost: 0,
                     });
                 }
                 
@@ -974,3 +1788,986 @@ fn check_color_conflict_fast(cells: &[usize]) -> bool {
     }
     false
 }
+
+/// Almost Locked Set: `cells.len()` cells in one unit holding exactly one more
+/// candidate than cells.
+#[derive(Debug, Clone)]
+pub struct Als {
+    pub cells: Vec<usize>,
+    pub mask: Candidates, // Union of candidates
+    pub cell_bits: u128, // Bit per cell index, for fast overlap tests
+}
+
+/// Enumerates every ALS of up to `max_size` cells, deduplicated across units
+/// (a set inside a box-line intersection is reported once).
+pub fn find_als(grid: &Grid, max_size: usize) -> Vec<Als> {
+    let mut result: Vec<Als> = Vec::new();
+
+    for unit in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()) {
+        let empty_cells: Vec<usize> = unit.iter().copied().filter(|&cell| grid.values[cell] == 0).collect();
+
+        // Each subset of the unit's empty cells is a bitmask over empty_cells
+        for subset in 1u32..(1 << empty_cells.len()) {
+            let size = subset.count_ones() as usize;
+            if size > max_size { continue; }
+
+            let members = || empty_cells.iter().enumerate().filter(move |&(i, _)| (subset >> i) & 1 == 1).map(|(_, &cell)| cell);
+            let mask = members().fold(Candidates::NONE, |mask, cell| mask | grid.candidates[cell]);
+            let cell_bits = members().fold(0u128, |bits, cell| bits | 1u128 << cell);
+
+            if mask.len() == size + 1 && !result.iter().any(|a| a.cell_bits == cell_bits) {
+                result.push(Als { cells: members().collect(), mask, cell_bits });
+            }
+        }
+    }
+    result
+}
+
+fn detect_death_blossom(grid: &Grid) -> Option<Hint> {
+    let all_als = find_als(grid, 4);
+    if all_als.len() < 2 { return None; }
+
+    for stem in 0..SIZE {
+        if grid.values[stem] != 0 { continue; }
+        let stem_mask = grid.candidates[stem];
+        let stem_count = stem_mask.len();
+        if !(2..=3).contains(&stem_count) { continue; }
+
+        let stem_digits: Vec<u8> = (1..=9u8).filter(|&d| stem_mask.contains(d)).collect();
+
+        // Petal candidates per stem digit: every cell of the ALS holding that digit sees the stem
+        let mut petals: Vec<Vec<&Als>> = Vec::with_capacity(stem_digits.len());
+        for &d in &stem_digits {
+            let list: Vec<&Als> = all_als
+                .iter()
+                .filter(|a| {
+                    (a.cell_bits >> stem) & 1 == 0
+                        && a.mask.contains(d)
+                        && a.cells.iter().all(|&c| !grid.candidates[c].contains(d) || can_see(c, stem))
+                })
+                .collect();
+            if list.is_empty() { break; }
+            petals.push(list);
+        }
+        if petals.len() != stem_digits.len() { continue; }
+
+        for z in 1..=9u8 {
+            if stem_mask.contains(z) { continue; }
+
+            let options: Vec<Vec<&Als>> = petals
+                .iter()
+                .map(|list| list.iter().copied().filter(|a| a.mask.contains(z)).collect())
+                .collect();
+            if options.iter().any(|o| o.is_empty()) { continue; }
+
+            let mut chosen: Vec<&Als> = Vec::with_capacity(options.len());
+            if let Some(h) = search_blossom(grid, stem, z, &options, &mut chosen) {
+                return Some(h);
+            }
+        }
+    }
+    None
+}
+
+fn search_blossom<'a>(grid: &Grid, stem: usize, z: u8, options: &[Vec<&'a Als>], chosen: &mut Vec<&'a Als>) -> Option<Hint> {
+    if chosen.len() == options.len() {
+        let petal_bits = chosen.iter().fold(0u128, |acc, a| acc | a.cell_bits);
+        let z_cells: Vec<usize> = chosen
+            .iter()
+            .flat_map(|a| a.cells.iter().copied())
+            .filter(|&c| grid.candidates[c].contains(z))
+            .collect();
+
+        let mut eliminations = Vec::new();
+        for cell in 0..SIZE {
+            if cell == stem || (petal_bits >> cell) & 1 == 1 { continue; }
+            if grid.values[cell] == 0 && grid.candidates[cell].contains(z) && z_cells.iter().all(|&zc| can_see(cell, zc)) {
+                eliminations.push((cell, z));
+            }
+        }
+
+        if !eliminations.is_empty() {
+            return Some(Hint {
+                difficulty: technique_difficulty("death_blossom"),
+                technique: "death_blossom",
+                eliminations,
+                placements: vec![],
+                base_cells: std::iter::once(stem).chain(chosen.iter().flat_map(|a| a.cells.iter().copied())).collect(),
+                subtype: 0,
+                search_cost: 0,
+            });
+        }
+        return None;
+    }
+
+    let used = chosen.iter().fold(0u128, |acc, a| acc | a.cell_bits);
+    for &als in &options[chosen.len()] {
+        if als.cell_bits & used != 0 { continue; } // Petals must be disjoint
+        chosen.push(als);
+        if let Some(h) = search_blossom(grid, stem, z, options, chosen) { return Some(h); }
+        chosen.pop();
+    }
+    None
+}
+
+/// Unique Rectangles: four unsolved cells spanning two rows, two columns and two
+/// boxes that all hold digits {a, b}. Reduced to just those two the puzzle would
+/// have two solutions, so (assuming uniqueness) the extra "roof" candidates
+/// cannot all disappear. The hint's `subtype` is the UR type, 1-6.
+fn detect_unique_rectangle(grid: &Grid) -> Option<Hint> {
+    for r1 in 0..9 {
+        for r2 in r1 + 1..9 {
+            for c1 in 0..9 {
+                for c2 in c1 + 1..9 {
+                    if (r1 / 3 == r2 / 3) == (c1 / 3 == c2 / 3) { continue; }
+
+                    // Corners in cyclic order, so k and k + 2 are diagonal
+                    let corners = [r1 * 9 + c1, r1 * 9 + c2, r2 * 9 + c2, r2 * 9 + c1];
+                    if corners.iter().any(|&c| grid.values[c] != 0) { continue; }
+                    let common = corners.iter().fold(Candidates::ALL, |acc, &c| acc & grid.candidates[c]);
+                    if common.len() < 2 { continue; }
+
+                    let digits: Vec<u8> = common.iter().collect();
+                    for i in 0..digits.len() {
+                        for j in i + 1..digits.len() {
+                            if let Some(h) = unique_rectangle_types(grid, &corners, digits[i], digits[j]) {
+                                return Some(h);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+fn unique_rectangle_types(grid: &Grid, corners: &[usize; 4], a: u8, b: u8) -> Option<Hint> {
+    let pair = Candidates::from_digit(a) | Candidates::from_digit(b);
+    let roof: Vec<usize> = corners.iter().copied().filter(|&c| grid.candidates[c] != pair).collect();
+    let hint = |subtype: u8, eliminations: Vec<(usize, u8)>| {
+        let extra = match subtype {
+            1 => 0.0,
+            2 | 4 => 1.0,
+            5 => 2.0,
+            _ => 3.0,
+        };
+        Some(Hint {
+            difficulty: technique_difficulty("unique_rectangle") + extra,
+            technique: "unique_rectangle",
+            eliminations,
+            placements: vec![],
+            base_cells: corners.to_vec(),
+            subtype,
+            search_cost: 0,
+        })
+    };
+
+    // Type 1: one roof cell, which must keep one of its extras
+    if roof.len() == 1 {
+        return hint(1, vec![(roof[0], a), (roof[0], b)]);
+    }
+    if roof.is_empty() { return None; }
+
+    // Types 2 and 5: every roof cell has the same single extra x, so x is in one of them
+    let extras = roof.iter().fold(Candidates::NONE, |acc, &c| acc | grid.candidates[c]) & !pair;
+    if let Some(x) = extras.single() {
+        let eliminations: Vec<(usize, u8)> = (0..SIZE)
+            .filter(|&cell| !corners.contains(&cell) && grid.values[cell] == 0 && grid.candidates[cell].contains(x))
+            .filter(|&cell| roof.iter().all(|&r| can_see(cell, r)))
+            .map(|cell| (cell, x))
+            .collect();
+        if !eliminations.is_empty() {
+            let subtype = if roof.len() == 2 && can_see(roof[0], roof[1]) { 2 } else { 5 };
+            return hint(subtype, eliminations);
+        }
+    }
+
+    if roof.len() != 2 { return None; }
+    let (p, q) = (roof[0], roof[1]);
+
+    if can_see(p, q) {
+        let mut units: Vec<&[usize; 9]> = Vec::with_capacity(2);
+        units.push(if p / 9 == q / 9 { &ROWS[p / 9] } else { &COLS[p % 9] });
+        let bx = box_of(p);
+        if bx == box_of(q) {
+            units.push(&BOXES[bx]);
+        }
+
+        // Type 3: the roof extras act as one cell in a naked subset with other cells of a shared unit
+        for unit in &units {
+            let others: Vec<usize> = unit.iter().copied().filter(|&c| c != p && c != q && grid.values[c] == 0).collect();
+            for subset in 1u32..(1 << others.len()) {
+                let size = subset.count_ones() as usize;
+                if size > 3 { continue; }
+                let in_subset = |k: usize| (subset >> k) & 1 == 1;
+                let union = others.iter().enumerate().filter(|&(k, _)| in_subset(k)).fold(extras, |union, (_, &c)| union | grid.candidates[c]);
+                if union.len() != size + 1 { continue; }
+
+                let eliminations: Vec<(usize, u8)> = others
+                    .iter()
+                    .enumerate()
+                    .filter(|&(k, _)| !in_subset(k))
+                    .flat_map(|(_, &c)| (grid.candidates[c] & union).iter().map(move |d| (c, d)))
+                    .collect();
+                if !eliminations.is_empty() {
+                    return hint(3, eliminations);
+                }
+            }
+        }
+
+        // Type 4: if u is locked to the roof in a shared unit, the other pair digit goes
+        for unit in &units {
+            for (u, v) in [(a, b), (b, a)] {
+                if unit.iter().all(|&c| c == p || c == q || grid.values[c] != 0 || !grid.candidates[c].contains(u)) {
+                    return hint(4, vec![(p, v), (q, v)]);
+                }
+            }
+        }
+    } else {
+        // Type 6: diagonal roof. If u is confined to the rectangle in both rows
+        // (or both columns), u on one roof cell puts it on the other too and
+        // the roof diagonal completes the deadly pattern. So u is forced onto
+        // the floor diagonal and leaves both roof cells.
+        let rows = [&ROWS[corners[0] / 9], &ROWS[corners[2] / 9]];
+        let cols = [&COLS[corners[0] % 9], &COLS[corners[2] % 9]];
+        for u in [a, b] {
+            let confined = |lines: [&[usize; 9]; 2]| {
+                lines.iter().all(|line| {
+                    line.iter().all(|&c| corners.contains(&c) || grid.values[c] != 0 || !grid.candidates[c].contains(u))
+                })
+            };
+            if confined(rows) || confined(cols) {
+                return hint(6, vec![(p, u), (q, u)]);
+            }
+        }
+    }
+    None
+}
+
+/// Avoidable Rectangle (Type 1): three corners of a two-box rectangle already
+/// solved (not given) as a/b/b, so the fourth can't be `a` without leaving a
+/// swappable deadly pattern. Assumes a unique solution. Reads `grid.givens`;
+/// a grid with none marked (e.g. parsed from candidates) can't tell clues
+/// from solved cells, so it gets no hint.
+pub fn detect_avoidable_rectangle(grid: &Grid) -> Option<Hint> {
+    if !grid.givens.contains(&true) { return None; }
+    let solved = |cell: usize| grid.values[cell] != 0 && !grid.givens[cell];
+
+    for r1 in 0..9 {
+        for r2 in r1 + 1..9 {
+            for c1 in 0..9 {
+                for c2 in c1 + 1..9 {
+                    // Exactly two boxes: rows share a band xor cols share a stack
+                    if (r1 / 3 == r2 / 3) == (c1 / 3 == c2 / 3) { continue; }
+
+                    let corners = [r1 * 9 + c1, r1 * 9 + c2, r2 * 9 + c2, r2 * 9 + c1];
+                    for k in 0..4 {
+                        let target = corners[k];
+                        let opposite = corners[(k + 2) % 4];
+                        let side1 = corners[(k + 1) % 4];
+                        let side2 = corners[(k + 3) % 4];
+
+                        if grid.values[target] != 0 { continue; }
+                        if !solved(opposite) || !solved(side1) || !solved(side2) { continue; }
+
+                        let a = grid.values[opposite];
+                        let b = grid.values[side1];
+                        if a == b || grid.values[side2] != b { continue; }
+
+                        if grid.candidates[target].contains(a) {
+                            return Some(Hint {
+                                difficulty: technique_difficulty("avoidable_rectangle"),
+                                technique: "avoidable_rectangle",
+                                eliminations: vec![(target, a)],
+                                placements: vec![],
+                                base_cells: vec![opposite, side1, side2],
+                                subtype: 0,
+                                search_cost: 0,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+const X_CYCLE_MAX_NODES: usize = 8;
+
+/// X-Cycles: single-digit loops of alternating strong (conjugate pair) and weak
+/// (shared unit) links. A continuous loop clears the digit from cells seeing both
+/// ends of any weak link; a discontinuity with two strong links places the digit,
+/// and one with two weak links removes it.
+fn detect_x_cycle(grid: &Grid) -> Option<Hint> {
+    for d in 1..=9u8 {
+        let cells: Vec<usize> = (0..SIZE).filter(|&i| grid.values[i] == 0 && grid.candidates[i].contains(d)).collect();
+
+        let mut strong: Vec<Vec<usize>> = vec![Vec::new(); SIZE];
+        for (a, b) in conjugate_pairs(grid, d) {
+            strong[a].push(b);
+            strong[b].push(a);
+        }
+
+        // Weak links only matter if the far end can continue with a strong link
+        let mut weak: Vec<Vec<usize>> = vec![Vec::new(); SIZE];
+        for &a in &cells {
+            for &b in &cells {
+                if a != b && !strong[b].is_empty() && can_see(a, b) {
+                    weak[a].push(b);
+                }
+            }
+        }
+
+        for &start in &cells {
+            if strong[start].is_empty() { continue; }
+            for first_strong in [true, false] {
+                let mut path = vec![start];
+                if let Some(h) = x_cycle_dfs(grid, d, &strong, &weak, &mut path, first_strong) {
+                    return Some(h);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn x_cycle_dfs(grid: &Grid, d: u8, strong: &[Vec<usize>], weak: &[Vec<usize>], path: &mut Vec<usize>, first_strong: bool) -> Option<Hint> {
+    let start = path[0];
+    let current = *path.last().unwrap();
+    // Link i is strong iff it has the same parity as the first link
+    let is_strong = |i: usize| i.is_multiple_of(2) == first_strong;
+    let next_link = path.len() - 1;
+
+    if path.len() >= 3 {
+        let closes = if is_strong(next_link) {
+            strong[current].contains(&start)
+        } else {
+            can_see(current, start)
+        };
+
+        if closes {
+            let continuous = is_strong(next_link) != first_strong;
+            if continuous && first_strong && path.len() >= 4 {
+                let mut eliminations = Vec::new();
+                for i in (1..path.len()).step_by(2) {
+                    let a = path[i];
+                    let b = if i + 1 < path.len() { path[i + 1] } else { start };
+                    for cell in 0..SIZE {
+                        if grid.values[cell] == 0 && grid.candidates[cell].contains(d) && !path.contains(&cell)
+                            && can_see(cell, a) && can_see(cell, b) && !eliminations.contains(&(cell, d)) {
+                            eliminations.push((cell, d));
+                        }
+                    }
+                }
+                if !eliminations.is_empty() {
+                    return Some(Hint {
+                        difficulty: technique_difficulty("x_cycle"),
+                        technique: "x_cycle",
+                        eliminations,
+                        placements: vec![],
+                        base_cells: path.clone(),
+                        subtype: 0,
+                        search_cost: 0,
+                    });
+                }
+            } else if !continuous && first_strong {
+                return Some(Hint {
+                    difficulty: technique_difficulty("x_cycle"),
+                    technique: "x_cycle",
+                    eliminations: vec![],
+                    placements: vec![(start, d)],
+                    base_cells: path.clone(),
+                    subtype: 0,
+                    search_cost: 0,
+                });
+            } else if !continuous {
+                return Some(Hint {
+                    difficulty: technique_difficulty("x_cycle"),
+                    technique: "x_cycle",
+                    eliminations: vec![(start, d)],
+                    placements: vec![],
+                    base_cells: path.clone(),
+                    subtype: 0,
+                    search_cost: 0,
+                });
+            }
+        }
+    }
+
+    if path.len() >= X_CYCLE_MAX_NODES { return None; }
+
+    let neighbors = if is_strong(next_link) { &strong[current] } else { &weak[current] };
+    for &next in neighbors {
+        if path.contains(&next) { continue; }
+        path.push(next);
+        if let Some(h) = x_cycle_dfs(grid, d, strong, weak, path, first_strong) { return Some(h); }
+        path.pop();
+    }
+    None
+}
+
+const AIC_MAX_NODES: usize = 12;
+
+/// Alternating Inference Chains over candidates `(cell, digit)`, stored as
+/// `cell * 9 + digit - 1`. Strong links are bivalue cells and conjugate pairs;
+/// weak links join candidates sharing a cell or (same digit) a unit. A chain of
+/// at most `max_len` nodes that starts and ends with a strong link makes one of
+/// its ends true, so anything both ends rule out goes. Difficulty grows by one
+/// per strong link beyond the second.
+pub fn detect_aic(grid: &Grid, max_len: usize) -> Option<Hint> {
+    let node = |cell: usize, d: u8| cell * 9 + (d - 1) as usize;
+    let peers: Vec<Vec<usize>> = (0..SIZE).map(get_peers).collect();
+
+    let mut strong: Vec<Vec<usize>> = vec![Vec::new(); SIZE * 9];
+    for d in 1..=9u8 {
+        for (a, b) in conjugate_pairs(grid, d) {
+            strong[node(a, d)].push(node(b, d));
+            strong[node(b, d)].push(node(a, d));
+        }
+    }
+    for cell in 0..SIZE {
+        if grid.values[cell] != 0 || grid.candidates[cell].len() != 2 { continue; }
+        let mut digits = grid.candidates[cell].iter();
+        let (a, b) = (digits.next().unwrap(), digits.next().unwrap());
+        strong[node(cell, a)].push(node(cell, b));
+        strong[node(cell, b)].push(node(cell, a));
+    }
+
+    let weak = |n: usize| -> Vec<usize> {
+        let (cell, d) = (n / 9, (n % 9) as u8 + 1);
+        let same_cell = grid.candidates[cell].iter().filter(move |&e| e != d).map(move |e| node(cell, e));
+        let same_digit = peers[cell].iter().filter(|&&p| grid.values[p] == 0 && grid.candidates[p].contains(d)).map(|&p| node(p, d));
+        same_cell.chain(same_digit).collect()
+    };
+
+    let mut best: Option<(usize, Hint)> = None;
+    let mut seen = vec![[false; 2]; SIZE * 9];
+    let mut parent = vec![[0usize; 2]; SIZE * 9];
+    for start in 0..SIZE * 9 {
+        if strong[start].is_empty() || grid.values[start / 9] != 0 { continue; }
+
+        // Breadth-first over (node, is_on) so the first conclusion from a start
+        // is its shortest chain. A start assumed off forces its strong partners on.
+        seen.fill([false; 2]);
+        seen[start][0] = true;
+        let mut frontier = vec![start];
+        let mut is_on = false;
+        for len in 2..=max_len {
+            if best.as_ref().is_some_and(|(l, _)| *l <= len) { break; }
+            let mut next = Vec::new();
+            for &n in &frontier {
+                let targets = if is_on { weak(n) } else { strong[n].clone() };
+                for t in targets {
+                    if !seen[t][!is_on as usize] {
+                        seen[t][!is_on as usize] = true;
+                        parent[t][!is_on as usize] = n;
+                        next.push(t);
+                    }
+                }
+            }
+            is_on = !is_on;
+            if next.is_empty() { break; }
+
+            if is_on && len >= 4 {
+                for &end in &next {
+                    if let Some(mut h) = aic_conclusion(grid, start, end, len) {
+                        // Walk back to the start, alternating on/off
+                        let (mut n, mut state) = (end, 1);
+                        let mut cells = vec![end / 9];
+                        for _ in 1..len {
+                            n = parent[n][state];
+                            state ^= 1;
+                            if cells.last() != Some(&(n / 9)) { cells.push(n / 9); }
+                        }
+                        cells.reverse();
+                        h.base_cells = cells;
+                        best = Some((len, h));
+                        break;
+                    }
+                }
+            }
+            frontier = next;
+        }
+        if best.as_ref().is_some_and(|(l, _)| *l == 4) { break; }
+    }
+    best.map(|(_, h)| h)
+}
+
+/// What a chain from `start` (off) to `end` (on) proves: at least one of the
+/// two candidates is true.
+fn aic_conclusion(grid: &Grid, start: usize, end: usize, len: usize) -> Option<Hint> {
+    let (c1, a) = (start / 9, (start % 9) as u8 + 1);
+    let (c2, b) = (end / 9, (end % 9) as u8 + 1);
+
+    let mut placements = Vec::new();
+    let mut eliminations = Vec::new();
+    if start == end {
+        placements.push((c1, a)); // Assuming it false proved it true
+    } else if a == b {
+        for cell in 0..SIZE {
+            if cell != c1 && cell != c2 && grid.values[cell] == 0 && grid.candidates[cell].contains(a)
+                && can_see(cell, c1) && can_see(cell, c2) {
+                eliminations.push((cell, a));
+            }
+        }
+    } else if c1 == c2 {
+        for d in grid.candidates[c1].iter() {
+            if d != a && d != b { eliminations.push((c1, d)); }
+        }
+    } else if can_see(c1, c2) {
+        if grid.candidates[c1].contains(b) { eliminations.push((c1, b)); }
+        if grid.candidates[c2].contains(a) { eliminations.push((c2, a)); }
+    }
+
+    if placements.is_empty() && eliminations.is_empty() { return None; }
+    Some(Hint {
+        difficulty: technique_difficulty("aic") + (len / 2 - 2) as f32,
+        technique: "aic",
+        eliminations,
+        placements,
+        base_cells: vec![],
+        subtype: 0,
+        search_cost: 0,
+    })
+}
+
+/// Cell Forcing Chains: for a cell with 2-3 candidates, follow each candidate
+/// through at most `max_len` forced singles. A candidate that leads to a
+/// contradiction is removed; otherwise any placement or elimination every
+/// branch agrees on is applied.
+pub fn detect_cell_forcing_chain(grid: &Grid, max_len: usize) -> Option<Hint> {
+    for cell in 0..SIZE {
+        if grid.values[cell] != 0 { continue; }
+        let mask = grid.candidates[cell];
+        if !(2..=3).contains(&mask.len()) { continue; }
+
+        let mut outcomes: Vec<Grid> = Vec::with_capacity(3);
+        let mut contradictions = Vec::new();
+        for d in 1..=9u8 {
+            if !mask.contains(d) { continue; }
+            match follow_singles(grid, cell, d, max_len) {
+                Some(g) => outcomes.push(g),
+                None => contradictions.push((cell, d)),
+            }
+        }
+
+        if !contradictions.is_empty() {
+            if outcomes.is_empty() { continue; } // Broken grid, not our call
+            return Some(Hint {
+                difficulty: technique_difficulty("cell_forcing_chain"),
+                technique: "cell_forcing_chain",
+                eliminations: contradictions,
+                placements: vec![],
+                base_cells: vec![cell],
+                subtype: 0,
+                search_cost: 0,
+            });
+        }
+
+        let mut placements = Vec::new();
+        let mut eliminations = Vec::new();
+        for target in 0..SIZE {
+            if target == cell || grid.values[target] != 0 { continue; }
+
+            let value = outcomes[0].values[target];
+            if value != 0 && outcomes.iter().all(|g| g.values[target] == value) {
+                placements.push((target, value));
+                continue;
+            }
+
+            for d in 1..=9u8 {
+                if !grid.candidates[target].contains(d) { continue; }
+                let ruled_out = |g: &Grid| if g.values[target] != 0 { g.values[target] != d } else { !g.candidates[target].contains(d) };
+                if outcomes.iter().all(ruled_out) {
+                    eliminations.push((target, d));
+                }
+            }
+        }
+
+        if !placements.is_empty() {
+            return Some(Hint {
+                difficulty: technique_difficulty("cell_forcing_chain"),
+                technique: "cell_forcing_chain",
+                eliminations: vec![],
+                placements,
+                base_cells: vec![cell],
+                subtype: 0,
+                search_cost: 0,
+            });
+        }
+        if !eliminations.is_empty() {
+            return Some(Hint {
+                difficulty: technique_difficulty("cell_forcing_chain"),
+                technique: "cell_forcing_chain",
+                eliminations,
+                placements: vec![],
+                base_cells: vec![cell],
+                subtype: 0,
+                search_cost: 0,
+            });
+        }
+    }
+    None
+}
+
+/// Places `digit` at `cell` and then up to `max_len` naked/hidden singles.
+/// Returns `None` if that produces a contradiction.
+fn follow_singles(grid: &Grid, cell: usize, digit: u8, max_len: usize) -> Option<Grid> {
+    let mut g = *grid;
+    g.set_value(cell, digit);
+    if !update_candidates_after_move(&mut g, cell, digit) { return None; }
+
+    for _ in 0..max_len {
+        let mut next = None;
+        for i in 0..SIZE {
+            if g.values[i] == 0 && g.candidates[i].len() == 1 {
+                next = Some((i, g.candidates[i].first().unwrap()));
+                break;
+            }
+        }
+        if next.is_none() {
+            for unit in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()) {
+                let mut placed = Candidates::NONE;
+                let mut seen_once = Candidates::NONE;
+                let mut seen_more = Candidates::NONE;
+                for &c in unit.iter() {
+                    if g.values[c] != 0 {
+                        placed.insert(g.values[c]);
+                    } else {
+                        seen_more |= seen_once & g.candidates[c];
+                        seen_once |= g.candidates[c];
+                    }
+                }
+                if placed | seen_once != Candidates::ALL { return None; } // Digit with nowhere to go
+
+                if let Some(d) = (seen_once & !seen_more & !placed).first() {
+                    let c = unit.iter().copied().find(|&c| g.values[c] == 0 && g.candidates[c].contains(d)).unwrap();
+                    next = Some((c, d));
+                    break;
+                }
+            }
+        }
+
+        match next {
+            Some((c, d)) => {
+                g.set_value(c, d);
+                if !update_candidates_after_move(&mut g, c, d) { return None; }
+            }
+            None => break,
+        }
+    }
+    Some(g)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden_single_ignores_unit_with_placed_digit() {
+        let mut grid = Grid::new();
+        grid.set_value(8, 5);
+        // r1c1 keeps a stale 5 while the rest of row 1 has it removed
+        for cell in 1..8 {
+            grid.candidates[cell].remove(5);
+        }
+        assert!(detect_hidden_single(&grid).is_none());
+    }
+
+    #[test]
+    fn hidden_single_reports_its_unit_kind() {
+        for (unit, kind) in [(ROWS[0], HIDDEN_IN_ROW), (COLS[0], HIDDEN_IN_COL), (BOXES[0], HIDDEN_IN_BOX)] {
+            let mut grid = Grid::new();
+            for &cell in &unit[1..] {
+                grid.candidates[cell].remove(5);
+            }
+            let hint = detect_hidden_single(&grid).unwrap();
+            assert_eq!((hint.placements[0], hint.subtype), ((0, 5), kind));
+            assert_eq!(detect_hidden_singles_all(&grid)[0].subtype, kind);
+        }
+    }
+
+    #[test]
+    fn ordered_hint_follows_the_given_order() {
+        let mut grid = Grid::from_string("4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......");
+        crate::solver::update_candidates(&mut grid);
+        let first = get_hint(&grid).unwrap();
+        assert_eq!(first.technique, "hidden_single");
+        let reordered = get_hint_ordered(&grid, &["pointing_pairs", "hidden_single"]).unwrap();
+        assert_eq!(reordered.technique, "pointing_pairs");
+        assert!(get_hint_ordered(&grid, &["no_such_technique", "x_wing"]).is_none());
+
+        while let Some(hint) = get_hint(&grid) {
+            let same = get_hint_ordered(&grid, &[hint.technique]).unwrap();
+            assert_eq!(same.placements, hint.placements);
+            assert_eq!(same.eliminations, hint.eliminations);
+            crate::difficulty::apply_hint(&mut grid, &hint);
+        }
+    }
+
+    #[test]
+    fn full_house_fills_last_cell_of_unit() {
+        let mut grid = Grid::from_string("12345678.");
+        crate::solver::update_candidates(&mut grid);
+        let hint = get_hint(&grid).unwrap();
+        assert_eq!(hint.technique, "full_house");
+        assert_eq!(hint.placements, vec![(8, 9)]);
+
+        // Row 1 and column 1 each miss one cell: both come back as one round
+        let mut grid = Grid::from_string("12345678.4........7........2........5........8........3........6.................");
+        crate::solver::update_candidates(&mut grid);
+        let round = get_all_hints_of_cheapest(&grid);
+        assert!(round.iter().all(|h| h.technique == "full_house"));
+        assert_eq!(round.iter().map(|h| h.placements[0]).collect::<Vec<_>>(), vec![(8, 9), (72, 9)]);
+    }
+
+    #[test]
+    fn notation_matches_sudokuwiki_log() {
+        let mut grid = Grid::new();
+        let pair = Candidates::from_digit(3) | Candidates::from_digit(7);
+        grid.candidates[1] = pair;
+        grid.candidates[4] = pair;
+        for (cell, digit) in [(0, 1), (2, 2), (3, 4), (5, 5), (6, 6), (8, 8)] {
+            grid.set_value(cell, digit);
+        }
+        grid.candidates[7] = pair | Candidates::from_digit(9);
+        let hint = detect_naked_subset(&grid, 2).unwrap();
+        assert_eq!(hint.to_notation(), "Naked Pair: 3,7 in r1c2,r1c5 removing 3,7 from r1c8");
+
+        let single = Hint { difficulty: 1.0, technique: "naked_single", eliminations: vec![], placements: vec![(80, 4)], base_cells: vec![], subtype: 0, search_cost: 0 };
+        assert_eq!(single.to_notation(), "Naked Single: placing 4 in r9c9");
+    }
+
+    #[test]
+    fn locked_pair_clears_line_and_box() {
+        let mut grid = Grid::new();
+        let pair = Candidates::from_digit(4) | Candidates::from_digit(6);
+        grid.candidates[0] = pair;
+        grid.candidates[1] = pair;
+        let hint = detect_naked_subset(&grid, 2).unwrap();
+        assert_eq!(hint.subtype, 1);
+        // Rest of row 1 and box 1, each cell once
+        assert_eq!(hint.eliminations.len(), (7 + 6) * 2);
+        assert!(hint.eliminations.contains(&(20, 4)) && hint.eliminations.contains(&(8, 6)));
+    }
+
+    #[test]
+    fn hint_from_candidates_keeps_crossed_out_marks() {
+        let mut candidates = [Candidates::ALL; SIZE];
+        for d in [1, 2, 3, 4, 6, 7, 8, 9] {
+            candidates[40].remove(d);
+        }
+        let hint = get_hint_from_candidates(&[0; SIZE], &candidates).unwrap();
+        assert_eq!((hint.technique, hint.placements), ("naked_single", vec![(40, 5)]));
+    }
+
+    #[test]
+    fn skyscraper_clears_cells_seeing_both_tops() {
+        let mut grid = Grid::new();
+        // Digit 1 only at r1c1/r1c5 and r5c1/r5c6; the shared c1 is the weak link
+        for c in 0..9 {
+            if c != 0 && c != 4 { grid.candidates[c].remove(1); }
+            if c != 0 && c != 5 { grid.candidates[36 + c].remove(1); }
+        }
+        let hint = detect_turbot_fish(&grid, TechniqueSet::ALL).unwrap();
+        assert_eq!(hint.technique, "skyscraper");
+        assert_eq!(hint.eliminations, vec![(14, 1), (23, 1), (31, 1), (49, 1)]);
+    }
+
+    #[test]
+    fn conjugate_pairs_match_unit_scan() {
+        let mut grid = Grid::from_string("49.7.....8..1..6.....82...192..6........4.7.2.....1..6..6.9.1..1......54..9.....8");
+        crate::solver::update_candidates(&mut grid);
+        let mut total = 0;
+        for d in 1..=9u8 {
+            let mut expected = Vec::new();
+            for unit in ROWS.iter().chain(COLS.iter()).chain(BOXES.iter()) {
+                let in_unit: Vec<usize> = unit.iter().copied().filter(|&c| grid.values[c] == 0 && grid.candidates[c].contains(d)).collect();
+                if in_unit.len() == 2 && !expected.contains(&(in_unit[0], in_unit[1])) {
+                    expected.push((in_unit[0], in_unit[1]));
+                }
+            }
+            total += expected.len();
+            assert_eq!(conjugate_pairs(&grid, d), expected);
+        }
+        assert!(total > 0);
+    }
+
+    #[test]
+    fn unique_rectangle_type1_clears_roof() {
+        let mut grid = Grid::new();
+        let pair = Candidates::from_digit(1) | Candidates::from_digit(2);
+        for cell in [0, 1, 27] {
+            grid.candidates[cell] = pair;
+        }
+        grid.candidates[28] = pair | Candidates::from_digit(3);
+        let hint = detect_unique_rectangle(&grid).unwrap();
+        assert_eq!(hint.subtype, 1);
+        assert_eq!(hint.eliminations, vec![(28, 1), (28, 2)]);
+    }
+
+    #[test]
+    fn avoidable_rectangle_needs_solved_corners() {
+        // r1c1=2, r1c2=1 and r4c2=2 were solved, so r4c1 can't be 1; r9c9 is a clue
+        let mut grid = Grid::new();
+        for (cell, digit) in [(0, 2), (1, 1), (28, 2), (80, 9)] {
+            grid.set_value(cell, digit);
+        }
+        grid.givens[80] = true;
+        crate::solver::update_candidates(&mut grid);
+        let hint = get_hint_with(&grid, TechniqueSet::AVOIDABLE_RECTANGLE).unwrap();
+        assert_eq!(hint.eliminations, vec![(27, 1)]);
+
+        grid.givens[1] = true;
+        assert!(detect_avoidable_rectangle(&grid).is_none());
+    }
+
+    #[test]
+    fn avoidable_rectangle_stays_off_without_known_givens() {
+        let mut puzzle = Grid::from_string(".1.369825...1........7243168.54371697.15864323.69127582.96435715.32916841.4875293");
+        crate::solver::update_candidates(&mut puzzle);
+        assert!(detect_avoidable_rectangle(&puzzle).is_none());
+        // Candidate strings don't say which values were clues
+        let imported = Grid::from_candidates_string(&puzzle.candidates_string()).unwrap();
+        assert!(detect_avoidable_rectangle(&imported).is_none());
+        assert!(get_hint(&imported).is_none_or(|h| h.technique != "avoidable_rectangle"));
+    }
+
+    #[test]
+    fn aic_links_bivalue_cells() {
+        let mut grid = Grid::new();
+        let digits = |a: u8, b: u8| Candidates::from_digit(a) | Candidates::from_digit(b);
+        grid.candidates[0] = digits(1, 2);
+        grid.candidates[4] = digits(2, 3);
+        grid.candidates[40] = digits(3, 1);
+        // r1c1=1 or r5c5=1, and r5c1 sees both
+        let hint = detect_aic(&grid, 6).unwrap();
+        assert_eq!(hint.eliminations, vec![(36, 1)]);
+        assert_eq!(hint.difficulty, technique_difficulty("aic") + 1.0);
+        assert!(detect_aic(&grid, 4).is_none());
+    }
+
+    #[test]
+    fn detectors_ignore_candidates_of_filled_cells() {
+        let mut grid = Grid::from_string("49.7.....8..1..6.....82...192..6........4.7.2.....1..6..6.9.1..1......54..9.....8");
+        crate::solver::update_candidates(&mut grid);
+        let mut stale = grid;
+        for cell in 0..SIZE {
+            if stale.values[cell] != 0 {
+                stale.candidates[cell] = Candidates::ALL;
+            }
+        }
+
+        let summary = |h: Option<Hint>| h.map(|h| (h.technique, h.subtype, h.eliminations, h.placements));
+        for &(name, _) in TECHNIQUES {
+            assert_eq!(summary(detect_technique(&stale, name)), summary(detect_technique(&grid, name)), "{}", name);
+        }
+        assert_eq!(summary(get_hint(&stale)), summary(get_hint(&grid)));
+    }
+
+    #[test]
+    fn empty_cell_without_candidates_is_a_contradiction() {
+        let mut grid = Grid::from_string("123456780000000000000000000000000000000000000000000000000000000000000000000000000");
+        crate::solver::update_candidates(&mut grid);
+        assert_eq!(find_contradiction(&grid), None);
+        grid.candidates[8] = Candidates::NONE;
+        assert_eq!(find_contradiction(&grid), Some(8));
+    }
+
+    #[test]
+    fn technique_set_follows_techniques_order() {
+        for &(name, _) in TECHNIQUES {
+            let flag = TechniqueSet::from_technique(name).unwrap();
+            assert_eq!(Some(flag), TechniqueSet::from_name(&name.to_uppercase()), "{}", name);
+            assert!(TechniqueSet::ALL.contains(flag));
+        }
+        assert_eq!(TechniqueSet::ALL.bits().count_ones() as usize, TECHNIQUES.len());
+        // Every technique has a detector on the ladder
+        let covered = LADDER.iter().fold(TechniqueSet::empty(), |all, &(set, _)| all | set);
+        assert_eq!(covered, TechniqueSet::ALL);
+        assert!(TechniqueSet::from_technique("no_such_technique").is_none());
+    }
+
+    #[test]
+    fn hint_with_skips_disabled_techniques() {
+        // Singles stall early on this one
+        let mut grid = Grid::from_string("4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......");
+        crate::solver::update_candidates(&mut grid);
+        while let Some(h) = get_hint_with(&grid, TechniqueSet::SINGLES) {
+            crate::difficulty::apply_hint(&mut grid, &h);
+        }
+        assert_eq!(get_hint(&grid).unwrap().technique, "pointing_pairs");
+        assert!(get_hint_with(&grid, TechniqueSet::empty()).is_none());
+    }
+
+    #[test]
+    fn hint_with_finds_triples_behind_an_earlier_pair() {
+        // The first box-line hit here is a pair; a triple follows it
+        let mut grid = Grid::from_string("48.3............71.2.......7.5....6....2..8.............1.76...3.....4......5....");
+        crate::solver::update_candidates(&mut grid);
+        while let Some(h) = get_hint_with(&grid, TechniqueSet::SINGLES) {
+            crate::difficulty::apply_hint(&mut grid, &h);
+        }
+        let family = TechniqueSet::BOX_LINE_REDUCTION | TechniqueSet::BOX_LINE_TRIPLES;
+        assert_eq!(get_hint_with(&grid, family).unwrap().technique, "box_line_reduction");
+        let triple = get_hint_with(&grid, TechniqueSet::BOX_LINE_TRIPLES).unwrap();
+        assert_eq!(triple.technique, "box_line_triples");
+        assert_eq!(detect_technique(&grid, "box_line_triples").unwrap().eliminations, triple.eliminations);
+    }
+
+    #[test]
+    fn all_findings_agree_with_first_and_hold_together() {
+        let mut grid = Grid::from_string("4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......");
+        crate::solver::update_candidates(&mut grid);
+        crate::solver::propagate_singles(&mut grid);
+        for name in ["pointing_pairs", "naked_pairs"] {
+            let all = detect_technique_all(&grid, name);
+            let Some(first) = detect_technique(&grid, name) else { continue };
+            assert!(all.iter().any(|h| h.eliminations == first.eliminations), "{}", name);
+        }
+
+        let all = get_all_hints_of_cheapest(&grid);
+        assert_eq!(all[0].technique, "pointing_pairs");
+        assert!(all.len() > 1);
+        let solution = crate::solver::solve(&grid).unwrap();
+        for hint in &all {
+            assert!(hint.eliminations.iter().all(|&(cell, d)| solution.values[cell] != d));
+        }
+    }
+
+    #[test]
+    fn candidate_cells_among_peers() {
+        // 1s in boxes 1 and 2 leave row 1's 1 to box 3
+        let mut grid = Grid::new();
+        grid.set_value(9, 1);
+        grid.set_value(22, 1);
+        crate::solver::update_candidates(&mut grid);
+        assert_eq!(cells_with_candidate(&grid, &ROWS[0], 1), vec![6, 7, 8]);
+        assert_eq!(cells_with_candidate(&grid, crate::utils::cells_seeing(8), 1), vec![6, 7, 35, 44, 53, 62, 71, 80]);
+    }
+
+    #[test]
+    fn transported_wing_reaches_past_the_pincers() {
+        // One row of candidate slots per grid row
+        let grid = Grid::from_candidates_string(concat!(
+            ".2.4.......3.5....1.....7.......6....23.......2..5.7.........8.1..4..7..........9",
+            "1....6.......56..91....67.9....5...9.......8....4......2.......1.....7....3......",
+            ".2.4.......3.....9.......8.1.........23.....9.2....7.9...4..7......5.........6...",
+            "1...5..........7.....4......2.......1.......91...5...9.....6.....3.............8.",
+            "1.3..6....2.......1.3..6.........7.....4.6..........8.1..4.............9....5....",
+            ".......8......6..91...56..9..3.5.......4.6...1.3.5....1..4..7.....4..7...2.......",
+            "......7..1.........2.........3.....9....5.........6.....3.....9.......8....4.....",
+            "..3.56......4.......3.56..........8.......7....3.....9..3.5...9.2.......1........",
+            "........9.......8...3.5.......4.....12.......12.........3.5.........6.........7..",
+        ))
+        .unwrap();
+        let hint = detect_transported_wing(&grid, TRANSPORT_MAX_LINKS).unwrap();
+        assert_eq!(hint.base_cells, vec![24, 16, 42]);
+        assert_eq!(hint.eliminations, vec![(38, 1)]);
+        // r5c3 doesn't see both pincers, so the plain Y-Wing can't remove it
+        assert!(!(can_see(38, 16) && can_see(38, 42)));
+        assert!(detect_y_wing(&grid).is_none_or(|h| !h.eliminations.contains(&(38, 1))));
+    }
+}
@@ -1,6 +1,4 @@
 
-use crate::grid::SIZE;
-
 pub const ROWS: [[usize; 9]; 9] = [
     [0, 1, 2, 3, 4, 5, 6, 7, 8],
     [9, 10, 11, 12, 13, 14, 15, 16, 17],
@@ -37,15 +35,45 @@ pub const BOXES: [[usize; 9]; 9] = [
     [60, 61, 62, 69, 70, 71, 78, 79, 80],
 ];
 
+/// The 20 peers of every cell in ascending order, built at compile time.
+static PEERS: [[usize; 20]; 81] = build_peers();
+
+const fn build_peers() -> [[usize; 20]; 81] {
+    let mut table = [[0usize; 20]; 81];
+    let mut cell = 0;
+    while cell < 81 {
+        let mut n = 0;
+        let mut other = 0;
+        while other < 81 {
+            let same_row = other / 9 == cell / 9;
+            let same_col = other % 9 == cell % 9;
+            let same_box = other / 27 == cell / 27 && other % 9 / 3 == cell % 9 / 3;
+            if other != cell && (same_row || same_col || same_box) {
+                table[cell][n] = other;
+                n += 1;
+            }
+            other += 1;
+        }
+        cell += 1;
+    }
+    table
+}
+
+/// Cells sharing a row, column or box with `cell`, ascending. The same set as
+/// `get_peers` without allocating.
+pub fn cells_seeing(cell: usize) -> &'static [usize; 20] {
+    &PEERS[cell]
+}
+
 pub fn get_peers(square: usize) -> Vec<usize> {
     let mut peers = Vec::with_capacity(20);
-    let row = square / 9;
-    let col = square % 9;
-    let box_idx = (row / 3) * 3 + (col / 3);
+    let row = row_of(square);
+    let col = col_of(square);
+    let box_idx = box_of(square);
 
     for i in 0..9 {
         let r = ROWS[row][i];
-        if r != square { peers.push(r); }
+        if r != square && !peers.contains(&r) { peers.push(r); }
         
         let c = COLS[col][i];
         if c != square && !peers.contains(&c) { peers.push(c); }
@@ -55,3 +83,78 @@ pub fn get_peers(square: usize) -> Vec<usize> {
     }
     peers
 }
+
+/// Zero-based row of a cell, indexing `ROWS`.
+pub fn row_of(cell: usize) -> usize {
+    cell / 9
+}
+
+/// Zero-based column of a cell, indexing `COLS`.
+pub fn col_of(cell: usize) -> usize {
+    cell % 9
+}
+
+/// Box of a cell, indexing `BOXES`: numbered left to right, top to bottom.
+pub fn box_of(cell: usize) -> usize {
+    (cell / 27) * 3 + (cell % 9) / 3
+}
+
+/// Zero-based `(row, col)` of a cell index.
+pub fn cell_to_rc(cell: usize) -> (usize, usize) {
+    (row_of(cell), col_of(cell))
+}
+
+pub fn rc_to_cell(r: usize, c: usize) -> usize {
+    r * 9 + c
+}
+
+/// One-based `r#c#` name of a cell, e.g. cell 33 is `"r4c7"`.
+pub fn cell_name(cell: usize) -> String {
+    let (r, c) = cell_to_rc(cell);
+    format!("r{}c{}", r + 1, c + 1)
+}
+
+/// Inverse of `cell_name` (case-insensitive). `None` unless both indices are 1-9.
+pub fn parse_cell_name(name: &str) -> Option<usize> {
+    let lower = name.trim().to_ascii_lowercase();
+    let (r, c) = lower.strip_prefix('r')?.split_once('c')?;
+    let (r, c): (usize, usize) = (r.parse().ok()?, c.parse().ok()?);
+    if !(1..=9).contains(&r) || !(1..=9).contains(&c) {
+        return None;
+    }
+    Some(rc_to_cell(r - 1, c - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peer_table_matches_get_peers() {
+        for cell in 0..81 {
+            let mut peers = get_peers(cell);
+            peers.sort_unstable();
+            assert_eq!(cells_seeing(cell).as_slice(), peers.as_slice());
+        }
+    }
+
+    #[test]
+    fn index_helpers_match_unit_tables() {
+        for unit in 0..9 {
+            for &cell in &ROWS[unit] { assert_eq!(row_of(cell), unit); }
+            for &cell in &COLS[unit] { assert_eq!(col_of(cell), unit); }
+            for &cell in &BOXES[unit] { assert_eq!(box_of(cell), unit); }
+        }
+    }
+
+    #[test]
+    fn cell_names_round_trip() {
+        assert_eq!(cell_name(33), "r4c7");
+        for cell in 0..81 {
+            assert_eq!(parse_cell_name(&cell_name(cell)), Some(cell));
+        }
+        assert_eq!(parse_cell_name("R1C1"), Some(0));
+        assert_eq!(parse_cell_name("r0c5"), None);
+        assert_eq!(parse_cell_name("r4"), None);
+    }
+}